@@ -7,8 +7,12 @@
 // SPDX-License-Identifier: MPL-2.0
 
 use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
 use quote::{quote, ToTokens};
-use syn::{parse_macro_input, AttrStyle, Attribute, Expr, ItemFn, Lit, Meta, MetaNameValue};
+use syn::{
+    meta::ParseNestedMeta, parse_macro_input, AttrStyle, Attribute, Expr, ExprArray, FnArg, Ident,
+    ItemFn, Lit, LitBool, LitStr, Meta, MetaNameValue, Type,
+};
 
 fn extract_doc_comment(attrs: &[Attribute]) -> impl Iterator<Item = String> + '_ {
     attrs
@@ -34,21 +38,179 @@ fn extract_doc_comment(attrs: &[Attribute]) -> impl Iterator<Item = String> + '_
         })
 }
 
+/// Returns the type of the parameter (i.e., `T` if the parameter is `&T`).
+fn parameter_type(arg: &FnArg) -> Option<&Type> {
+    match arg {
+        FnArg::Typed(pat_type) => match pat_type.ty.as_ref() {
+            Type::Reference(reference) => Some(reference.elem.as_ref()),
+            ty => Some(ty),
+        },
+        FnArg::Receiver(_) => None,
+    }
+}
+
+/// Returns the name of a type without path and generics (e.g., `PolicyContext` for
+/// `crate::PolicyContext<'_>`).
+fn type_name(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Path(path) => Some(path.path.segments.last()?.ident.to_string()),
+        _ => None,
+    }
+}
+
+/// Metadata of a policy, as passed to the attribute (e.g.,
+/// `#[policy(severity = "high", confidence = "medium", category = "injection", tags = ["foo"])]`).
+#[derive(Default)]
+struct PolicyArgs {
+    severity: Option<Ident>,
+    confidence: Option<Ident>,
+    category: Option<LitStr>,
+    tags: Vec<LitStr>,
+    /// Whether the violations are reported by the engine, which calls the function itself (e.g.,
+    /// because it depends on the results of other policies).
+    reported_by_engine: bool,
+    /// Whether the policy is checked unless it is configured otherwise (e.g., `enabled = false`
+    /// for strict variants of other policies).
+    enabled: Option<LitBool>,
+}
+
+/// Converts a level (e.g. `"high"`) into the corresponding variant identifier (e.g. `High`).
+fn parse_level(value: &LitStr, levels: &[&str]) -> syn::Result<Ident> {
+    let level = value.value();
+    if !levels.contains(&level.as_str()) {
+        return Err(syn::Error::new(
+            value.span(),
+            format!("expected one of {}", levels.join(", ")),
+        ));
+    }
+    let variant = level[..1].to_uppercase() + &level[1..];
+    Ok(Ident::new(&variant, value.span()))
+}
+
+impl PolicyArgs {
+    fn parse(&mut self, meta: ParseNestedMeta) -> syn::Result<()> {
+        if meta.path.is_ident("severity") {
+            let value: LitStr = meta.value()?.parse()?;
+            self.severity = Some(parse_level(&value, &["info", "low", "medium", "high"])?);
+        } else if meta.path.is_ident("confidence") {
+            let value: LitStr = meta.value()?.parse()?;
+            self.confidence = Some(parse_level(&value, &["low", "medium", "high"])?);
+        } else if meta.path.is_ident("category") {
+            self.category = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("tags") {
+            let tags: ExprArray = meta.value()?.parse()?;
+            self.tags = tags
+                .elems
+                .into_iter()
+                .map(|tag| match tag {
+                    Expr::Lit(syn::ExprLit {
+                        lit: Lit::Str(tag), ..
+                    }) => Ok(tag),
+                    tag => Err(syn::Error::new_spanned(tag, "expected a string literal")),
+                })
+                .collect::<syn::Result<_>>()?;
+        } else if meta.path.is_ident("enabled") {
+            self.enabled = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("reported_by_engine") {
+            self.reported_by_engine = true;
+        } else {
+            return Err(meta.error("unsupported policy property"));
+        }
+        Ok(())
+    }
+
+    /// Returns the builder method calls that set the metadata.
+    fn to_builder_calls(&self) -> TokenStream2 {
+        let severity = self
+            .severity
+            .as_ref()
+            .map(|severity| quote!(.with_severity(crate::Severity::#severity)));
+        let confidence = self
+            .confidence
+            .as_ref()
+            .map(|confidence| quote!(.with_confidence(crate::Confidence::#confidence)));
+        let category = self
+            .category
+            .as_ref()
+            .map(|category| quote!(.with_category(#category)));
+        let tags = &self.tags;
+        let tags = (!tags.is_empty()).then(|| quote!(.with_tags(&[#(#tags),*])));
+        let enabled = self
+            .enabled
+            .as_ref()
+            .map(|enabled| quote!(.with_enabled(#enabled)));
+        quote!(#severity #confidence #category #tags #enabled)
+    }
+}
+
 #[proc_macro_attribute]
-pub fn policy(_args: TokenStream, input: TokenStream) -> TokenStream {
+pub fn policy(args: TokenStream, input: TokenStream) -> TokenStream {
+    let mut policy_args = PolicyArgs::default();
+    let args_parser = syn::meta::parser(|meta| policy_args.parse(meta));
+    parse_macro_input!(args with args_parser);
+    let metadata = policy_args.to_builder_calls();
+    let reported_by_engine = policy_args.reported_by_engine;
     let item_fn = parse_macro_input!(input as ItemFn);
     let doc: String = extract_doc_comment(&item_fn.attrs).fold(String::new(), |a, b| a + &b + "\n");
     let ident = item_fn.sig.ident.clone();
     let policy_name = ident.to_token_stream().to_string();
 
+    // Policies either check workflows, actions or any kind of document. Other kinds of documents
+    // do not violate the policy.
+    let mut parameters = item_fn.sig.inputs.iter().map(parameter_type);
+    let document = match parameters.next().flatten().and_then(type_name).as_deref() {
+        Some("Document") => quote!(Some(document)),
+        Some("Action") => quote!(document.as_action()),
+        _ => quote!(document.as_workflow()),
+    };
+
+    // Policies may take the context and/or their options as further parameters (in any order).
+    let mut options_type = None;
+    let arguments: Vec<_> = parameters
+        .flatten()
+        .map(|ty| match type_name(ty).as_deref() {
+            Some("PolicyContext") => quote!(context),
+            _ => {
+                options_type = Some(ty.clone());
+                quote!(&context.options().get::<#ty>())
+            }
+        })
+        .collect();
+    let context = if arguments.is_empty() {
+        quote!(_context)
+    } else {
+        quote!(context)
+    };
+    let with_options = options_type.map(|options_type| quote!(.with_options::<#options_type>()));
+    let new_policy = if reported_by_engine {
+        quote!(crate::Policy::reported_by_engine(#policy_name) #metadata)
+    } else {
+        quote!(
+            crate::Policy::new(#policy_name, |document, #context| {
+                #document.map_or_else(Vec::new, |document| #ident(document, #(#arguments),*))
+            })
+            #with_options
+            #metadata
+        )
+    };
+
+    // Every policy gets a test that checks the examples in its documentation.
+    let test_ident = Ident::new(&format!("test_{policy_name}_doc_examples"), ident.span());
+
     quote!(
         #item_fn
 
+        #[cfg(test)]
+        #[test]
+        fn #test_ident() {
+            crate::doc_examples::check(#policy_name);
+        }
+
         inventory::submit! {
             if #doc.is_empty() {
-               crate::Policy::new(#policy_name, #ident)
+               #new_policy
             } else {
-               crate::Policy::new(#policy_name, #ident).with_doc(#doc)
+               #new_policy.with_doc(#doc)
             }
         }
     )
@@ -0,0 +1,222 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Baseline files, which record existing findings so that only new findings are reported.
+
+use crate::output::{normalize_path, Finding};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::Path;
+
+/// The version of the baseline file format.
+///
+/// The fingerprints are derived from the policy, the path in the repository, the job, the step
+/// and the key of the violation.
+const VERSION: u32 = 1;
+
+/// A finding recorded in a baseline.
+///
+/// Only the fingerprint is used for matching, the other fields make the file easier to review.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct BaselineEntry {
+    fingerprint: String,
+    policy: String,
+    file: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    job: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    step: Option<String>,
+    message: String,
+}
+
+impl From<&Finding> for BaselineEntry {
+    fn from(finding: &Finding) -> Self {
+        Self {
+            fingerprint: finding.fingerprint(),
+            policy: finding.policy().name.to_owned(),
+            file: normalize_path(finding.repository_path()),
+            job: finding.violation().job().map(str::to_owned),
+            step: finding.violation().step().map(str::to_owned),
+            message: finding.violation().message().to_owned(),
+        }
+    }
+}
+
+/// A set of known findings.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Baseline {
+    version: u32,
+    findings: Vec<BaselineEntry>,
+}
+
+impl Baseline {
+    /// Creates a baseline that contains the given findings.
+    pub fn from_findings(findings: &[Finding]) -> Self {
+        Self {
+            version: VERSION,
+            findings: findings.iter().map(BaselineEntry::from).collect(),
+        }
+    }
+
+    /// Reads a baseline from a file.
+    pub fn from_path(path: impl AsRef<Path>) -> crate::Result<Self> {
+        let file = File::open(path)?;
+        let baseline: Baseline = serde_json::from_reader(BufReader::new(file))?;
+        if baseline.version != VERSION {
+            return Err(crate::Error::BaselineVersionError(baseline.version));
+        }
+        Ok(baseline)
+    }
+
+    /// Writes the baseline to a file.
+    pub fn write(&self, path: impl AsRef<Path>) -> crate::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut writer, self)?;
+        writeln!(writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// The number of findings in this baseline.
+    pub fn len(&self) -> usize {
+        self.findings.len()
+    }
+
+    /// Returns `true` if the baseline does not contain any findings.
+    pub fn is_empty(&self) -> bool {
+        self.findings.is_empty()
+    }
+
+    /// Removes all findings that are contained in the baseline.
+    ///
+    /// If the baseline contains a fingerprint `n` times, only the first `n` findings with that
+    /// fingerprint are removed, so that additional identical findings are still reported.
+    pub fn filter(&self, findings: Vec<Finding>) -> Vec<Finding> {
+        let mut known = self
+            .findings
+            .iter()
+            .fold(HashMap::new(), |mut known, entry| {
+                *known.entry(entry.fingerprint.as_str()).or_insert(0usize) += 1;
+                known
+            });
+        findings
+            .into_iter()
+            .filter(
+                |finding| match known.get_mut(finding.fingerprint().as_str()) {
+                    Some(count) if *count > 0 => {
+                        *count -= 1;
+                        false
+                    }
+                    _ => true,
+                },
+            )
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::policies::{get_policies, PolicyCheckOutput, PolicyViolation};
+    use crate::Repository;
+    use marked_yaml::{Marker, Span};
+
+    fn findings(lines: &[usize]) -> Vec<Finding> {
+        findings_in_step(lines, None)
+    }
+
+    fn findings_in_step(lines: &[usize], step: Option<&str>) -> Vec<Finding> {
+        findings_in_file(
+            lines,
+            step,
+            Path::new("workflow.yml"),
+            &Repository::default(),
+        )
+    }
+
+    fn findings_in_file(
+        lines: &[usize],
+        step: Option<&str>,
+        path: &Path,
+        repository: &Repository,
+    ) -> Vec<Finding> {
+        let policy = get_policies().next().unwrap();
+        let violations = lines
+            .iter()
+            .map(|&line| {
+                let marker = Marker::new(0, line, 1);
+                let violation = PolicyViolation::new(
+                    Span::new_with_marks(marker, marker),
+                    format!("Something is wrong in line {line}."),
+                )
+                .with_job("build")
+                .with_key("something");
+                match step {
+                    Some(step) => violation.with_step(step),
+                    None => violation,
+                }
+            })
+            .collect();
+        Finding::from_outputs(
+            path,
+            repository,
+            &[PolicyCheckOutput::new(policy, violations)],
+        )
+    }
+
+    #[test]
+    fn test_filter() {
+        let baseline = Baseline::from_findings(&findings(&[1, 2]));
+        assert!(baseline.filter(findings(&[3, 4])).is_empty());
+        assert_eq!(baseline.filter(findings(&[3, 4, 5])).len(), 1);
+    }
+
+    #[test]
+    fn test_filter_moved_step() {
+        // The message changes if a step is inserted before the step with the ID `test`.
+        let baseline = Baseline::from_findings(&findings_in_step(&[10], Some("test")));
+        assert!(baseline
+            .filter(findings_in_step(&[14], Some("test")))
+            .is_empty());
+        assert_eq!(
+            baseline.filter(findings_in_step(&[14], Some("lint"))).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_filter_other_path() {
+        // The same file, passed in a different way or checked out in a different directory.
+        let findings = |path: &str, root: &str| {
+            findings_in_file(&[1], None, Path::new(path), &Repository::new(root))
+        };
+        let baseline =
+            Baseline::from_findings(&findings("/repo/./.github/workflows/build.yml", "/repo"));
+        assert_eq!(baseline.findings[0].file, ".github/workflows/build.yml");
+        assert!(baseline
+            .filter(findings(
+                "/repo/docs/../.github/workflows/build.yml",
+                "/repo"
+            ))
+            .is_empty());
+        assert!(baseline
+            .filter(findings(
+                "/checkout/.github/workflows/build.yml",
+                "/checkout"
+            ))
+            .is_empty());
+        assert_eq!(
+            baseline
+                .filter(findings("/repo/.github/workflows/test.yml", "/repo"))
+                .len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,302 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Configuration file (`.ghastly.toml`).
+//!
+//! ```toml
+//! # Policies are configured by name.
+//! [policies.permissions_set]
+//! enabled = false
+//!
+//! [policies.actions_pinned]
+//! severity = "high"
+//! # Policy-specific options.
+//! trusted-actions = ["actions/*"]
+//!
+//! # Overrides apply to files that match one of the globs (relative to the configuration file).
+//! [[overrides]]
+//! paths = [".github/workflows/legacy-*.yml"]
+//!
+//! [overrides.policies.actions_pinned]
+//! enabled = false
+//! ```
+
+use crate::policies::{get_policies, Policy, PolicyOptions, Severity};
+use crate::Error;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{self, Component, Path, PathBuf};
+
+/// The name of the configuration file that is discovered automatically.
+pub const CONFIG_FILE_NAME: &str = ".ghastly.toml";
+
+/// The configuration of a policy.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "kebab-case")]
+pub struct PolicyConfig {
+    /// Whether the policy is checked (default: whether the policy is enabled by default).
+    enabled: Option<bool>,
+    /// Overrides the severity of all violations of the policy.
+    severity: Option<Severity>,
+    /// Policy-specific options.
+    #[serde(flatten)]
+    options: toml::Table,
+}
+
+impl PolicyConfig {
+    /// Returns `true` if the policy is checked, which depends on the policy unless it is enabled
+    /// or disabled explicitly (see [`Policy::enabled`]).
+    pub fn is_enabled(&self, policy: &Policy) -> bool {
+        self.enabled.unwrap_or(policy.enabled)
+    }
+
+    /// The configured severity (if any).
+    pub const fn severity(&self) -> Option<Severity> {
+        self.severity
+    }
+
+    /// The policy-specific options.
+    pub fn options(&self) -> PolicyOptions {
+        PolicyOptions::new(self.options.clone())
+    }
+
+    /// Applies the settings of the other configuration on top of this one.
+    fn merge(&mut self, other: &PolicyConfig) {
+        self.enabled = other.enabled.or(self.enabled);
+        self.severity = other.severity.or(self.severity);
+        self.options.extend(
+            other
+                .options
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone())),
+        );
+    }
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+struct ConfigSyntax {
+    #[serde(default)]
+    policies: BTreeMap<String, PolicyConfig>,
+    #[serde(default)]
+    overrides: Vec<OverrideSyntax>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct OverrideSyntax {
+    paths: Vec<String>,
+    #[serde(default)]
+    policies: BTreeMap<String, PolicyConfig>,
+}
+
+/// Policy settings that only apply to some files.
+#[derive(Debug)]
+struct Override {
+    paths: GlobSet,
+    policies: BTreeMap<String, PolicyConfig>,
+}
+
+/// The configuration of ghastly.
+#[derive(Debug)]
+pub struct Config {
+    /// The directory that the globs of overrides are relative to.
+    root: PathBuf,
+    policies: BTreeMap<String, PolicyConfig>,
+    overrides: Vec<Override>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            root: PathBuf::from("."),
+            policies: BTreeMap::new(),
+            overrides: Vec::new(),
+        }
+    }
+}
+
+/// Returns an error if a policy does not exist or its options are invalid.
+fn validate_policies(policies: &BTreeMap<String, PolicyConfig>) -> crate::Result<()> {
+    policies.iter().try_for_each(|(name, policy_config)| {
+        let policy = get_policies()
+            .find(|policy| policy.name == name)
+            .ok_or_else(|| Error::ConfigError(format!("unknown policy '{name}'")))?;
+        policy
+            .validate_options(&policy_config.options())
+            .map_err(|err| {
+                Error::ConfigError(format!("invalid options for policy '{name}': {err}"))
+            })
+    })
+}
+
+impl Config {
+    /// Parses the configuration, with globs relative to the given directory.
+    pub fn from_str(value: &str, root: impl Into<PathBuf>) -> crate::Result<Self> {
+        let syntax: ConfigSyntax = toml::from_str(value)?;
+        validate_policies(&syntax.policies)?;
+        let overrides = syntax
+            .overrides
+            .into_iter()
+            .map(|override_syntax| {
+                validate_policies(&override_syntax.policies)?;
+                let paths = override_syntax
+                    .paths
+                    .iter()
+                    .try_fold(GlobSetBuilder::new(), |mut builder, glob| {
+                        let glob = GlobBuilder::new(glob)
+                            .literal_separator(true)
+                            .build()
+                            .map_err(|err| Error::ConfigError(err.to_string()))?;
+                        builder.add(glob);
+                        Ok::<_, Error>(builder)
+                    })?
+                    .build()
+                    .map_err(|err| Error::ConfigError(err.to_string()))?;
+                Ok(Override {
+                    paths,
+                    policies: override_syntax.policies,
+                })
+            })
+            .collect::<crate::Result<_>>()?;
+
+        Ok(Self {
+            root: root.into(),
+            policies: syntax.policies,
+            overrides,
+        })
+    }
+
+    /// Reads the configuration from a file.
+    pub fn from_path(path: impl AsRef<Path>) -> crate::Result<Self> {
+        let path = path.as_ref();
+        let root = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        Self::from_str(&fs::read_to_string(path)?, root)
+    }
+
+    /// Searches the given directory and its parents (up to the root of the Git repository) for a
+    /// configuration file and reads it.
+    pub fn discover(directory: impl AsRef<Path>) -> crate::Result<Option<Self>> {
+        let directory = path::absolute(directory)?;
+        for ancestor in directory.ancestors() {
+            let path = ancestor.join(CONFIG_FILE_NAME);
+            if path.is_file() {
+                return Self::from_path(path).map(Some);
+            }
+            if ancestor.join(".git").exists() {
+                break;
+            }
+        }
+        Ok(None)
+    }
+
+    /// The effective configuration of the policy for the given file.
+    pub fn policy_config(&self, name: &str, path: &Path) -> PolicyConfig {
+        let mut policy_config = self.policies.get(name).cloned().unwrap_or_default();
+        let path = self.relative_path(path);
+        self.overrides
+            .iter()
+            .filter(|override_config| override_config.paths.is_match(&path))
+            .filter_map(|override_config| override_config.policies.get(name))
+            .for_each(|override_policy_config| policy_config.merge(override_policy_config));
+        policy_config
+    }
+
+    /// Returns the path relative to the root directory (if possible).
+    fn relative_path(&self, path: &Path) -> PathBuf {
+        let path = absolute_path(path);
+        path.strip_prefix(absolute_path(&self.root))
+            .map(Path::to_owned)
+            .unwrap_or(path)
+    }
+}
+
+/// Makes the path absolute and removes `.` and `..` components (without resolving symlinks).
+pub(crate) fn absolute_path(path: &Path) -> PathBuf {
+    let path = path::absolute(path).unwrap_or_else(|_| path.to_owned());
+    path.components()
+        .fold(PathBuf::new(), |mut normalized, component| {
+            match component {
+                Component::CurDir => (),
+                Component::ParentDir => {
+                    normalized.pop();
+                }
+                component => normalized.push(component),
+            };
+            normalized
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_policy_config() {
+        let config = Config::from_str(
+            r#"
+[policies.permissions_set]
+enabled = false
+
+[policies.actions_pinned]
+severity = "high"
+trusted-actions = ["actions/*"]
+
+[[overrides]]
+paths = [".github/workflows/legacy-*.yml"]
+
+[overrides.policies.actions_pinned]
+enabled = false
+"#,
+            "/repo",
+        )
+        .unwrap();
+
+        let policy = |name| get_policies().find(|policy| policy.name == name).unwrap();
+        assert!(!config
+            .policy_config(
+                "permissions_set",
+                Path::new("/repo/.github/workflows/build.yml")
+            )
+            .is_enabled(policy("permissions_set")));
+        // Strict policies are disabled by default.
+        assert!(!config
+            .policy_config(
+                "no_github_expr_in_run",
+                Path::new("/repo/.github/workflows/build.yml")
+            )
+            .is_enabled(policy("no_github_expr_in_run")));
+
+        let policy_config = config.policy_config(
+            "actions_pinned",
+            Path::new("/repo/.github/workflows/build.yml"),
+        );
+        assert!(policy_config.is_enabled(policy("actions_pinned")));
+        assert_eq!(policy_config.severity(), Some(Severity::High));
+
+        let policy_config = config.policy_config(
+            "actions_pinned",
+            Path::new("/repo/.github/workflows/legacy-build.yml"),
+        );
+        assert!(!policy_config.is_enabled(policy("actions_pinned")));
+        assert_eq!(policy_config.severity(), Some(Severity::High));
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        assert!(Config::from_str("[policies.does_not_exist]\nenabled = false\n", ".").is_err());
+        assert!(Config::from_str("[policies.permissions_set]\nfoo = 1\n", ".").is_err());
+        assert!(Config::from_str("[policies.actions_pinned]\ntrusted-actions = 1\n", ".").is_err());
+        assert!(Config::from_str("[[overrides]]\npaths = [\"[\"]\n", ".").is_err());
+    }
+}
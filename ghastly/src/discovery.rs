@@ -0,0 +1,114 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::config::absolute_path;
+use ignore::WalkBuilder;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+/// The kind of a file that can be checked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    /// A workflow file, e.g. `.github/workflows/build.yml`.
+    Workflow,
+    /// An action metadata file, e.g. `.github/actions/foo/action.yml`.
+    Action,
+}
+
+impl FileKind {
+    /// Determines the kind of a file from its path.
+    ///
+    /// Returns `None` if the file is neither located in a `.github/workflows` directory nor named
+    /// `action.yml` or `action.yaml`.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let file_name = path.file_name().and_then(OsStr::to_str)?;
+        if matches!(file_name, "action.yml" | "action.yaml") {
+            return Some(FileKind::Action);
+        }
+
+        let is_yaml = matches!(
+            path.extension().and_then(OsStr::to_str),
+            Some("yml" | "yaml")
+        );
+        let mut parents = path.parent().into_iter().flat_map(Path::components).rev();
+        let is_in_workflows_dir = parents
+            .next()
+            .is_some_and(|component| component.as_os_str() == "workflows")
+            && parents
+                .next()
+                .is_some_and(|component| component.as_os_str() == ".github");
+        (is_yaml && is_in_workflows_dir).then_some(FileKind::Workflow)
+    }
+}
+
+/// Discovers all files to check in the given paths.
+///
+/// Files are always returned, and treated as workflow unless they are named like an action
+/// metadata file. Directories are searched recursively for workflows and action metadata files
+/// (see [`FileKind::from_path`]), skipping files that are ignored by `.gitignore`. The kind of a
+/// file is determined from its absolute path, so that workflows are found when running in (or
+/// passing) a subdirectory of `.github`.
+pub fn discover_files<P>(paths: &[P]) -> impl Iterator<Item = crate::Result<(PathBuf, FileKind)>>
+where
+    P: AsRef<Path>,
+{
+    let mut builder = WalkBuilder::new(paths.first().map_or(Path::new("."), AsRef::as_ref));
+    paths.iter().skip(1).for_each(|path| {
+        builder.add(path);
+    });
+    builder
+        .hidden(false)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .build()
+        .filter_map(|entry| {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(err) => return Some(Err(err.into())),
+            };
+            let is_file = entry
+                .file_type()
+                .is_some_and(|file_type| file_type.is_file());
+            if !is_file {
+                return None;
+            }
+            let kind = FileKind::from_path(&absolute_path(entry.path()));
+            if entry.depth() == 0 {
+                // Files that are given explicitly are always checked.
+                return Some(Ok((entry.into_path(), kind.unwrap_or(FileKind::Workflow))));
+            }
+            kind.map(|kind| Ok((entry.into_path(), kind)))
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_file_kind_from_path() {
+        assert_eq!(
+            FileKind::from_path(Path::new("repo/.github/workflows/build.yml")),
+            Some(FileKind::Workflow)
+        );
+        assert_eq!(
+            FileKind::from_path(Path::new(".github/workflows/build.yaml")),
+            Some(FileKind::Workflow)
+        );
+        assert_eq!(
+            FileKind::from_path(Path::new(".github/actions/foo/action.yml")),
+            Some(FileKind::Action)
+        );
+        assert_eq!(
+            FileKind::from_path(Path::new(".github/workflows/README.md")),
+            None
+        );
+        assert_eq!(FileKind::from_path(Path::new("workflows/build.yml")), None);
+        assert_eq!(FileKind::from_path(Path::new("build.yml")), None);
+    }
+}
@@ -0,0 +1,242 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Checks the examples in the documentation of policies.
+//!
+//! The `#[policy]` attribute generates a test for every policy that calls [`check`].
+//!
+//! An example may consist of several files of a repository, each in its own code block that
+//! starts with a `# file: <path>` comment. The first file of an example is checked.
+
+use crate::parser::document::Document;
+use crate::{
+    check_source, fix_source, get_policies, Config, FileKind, Repository, WORKFLOW_SCHEMA,
+};
+use std::path::{Path, PathBuf};
+
+/// The root directory of the repository that the files of an example are part of.
+const EXAMPLE_ROOT: &str = "/example";
+
+/// Whether an example should violate the policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Expectation {
+    Violation,
+    NoViolation,
+}
+
+/// An example from the documentation of a policy.
+struct Example<'a> {
+    title: &'a str,
+    expectation: Expectation,
+    /// The path of the checked file, relative to the repository root.
+    path: String,
+    source: String,
+    /// The paths and sources of the other files in the repository.
+    other_files: Vec<(String, String)>,
+}
+
+impl Example<'_> {
+    /// The absolute path of the checked file.
+    fn path(&self) -> PathBuf {
+        Path::new(EXAMPLE_ROOT).join(&self.path)
+    }
+
+    /// The repository of the example, with the given source of the checked file.
+    fn repository(&self, source: &str) -> Repository {
+        let mut repository = Repository::new(EXAMPLE_ROOT);
+        for (path, other_source) in &self.other_files {
+            let document =
+                Document::from_reader(&mut other_source.as_bytes(), example_kind(other_source))
+                    .unwrap_or_else(|err| {
+                        panic!(
+                            "File {path} of example \"{}\" is invalid: {err}",
+                            self.title
+                        )
+                    });
+            repository.insert(Path::new(EXAMPLE_ROOT).join(path), document);
+        }
+        // Invalid files are reported by the `workflow_schema` policy instead.
+        if let Ok(document) = Document::from_reader(&mut source.as_bytes(), example_kind(source)) {
+            repository.insert(self.path(), document);
+        }
+        repository
+    }
+}
+
+/// Extracts the YAML code blocks in the `## Not OK` and `## OK` sections of the documentation.
+fn extract_examples(doc: &str) -> Vec<Example<'_>> {
+    let mut examples: Vec<Example> = Vec::new();
+    let mut section = None;
+    let mut section_has_example = false;
+    let mut code_block: Option<String> = None;
+    for line in doc.lines() {
+        if let Some(source) = code_block.as_mut() {
+            if line.trim_start().starts_with("```") {
+                let source = code_block.take().unwrap_or_default();
+                let path = source
+                    .lines()
+                    .next()
+                    .and_then(|line| line.strip_prefix("# file: "))
+                    .map(str::to_owned);
+                match (section, path) {
+                    (Some(_), Some(path)) if section_has_example => {
+                        if let Some(example) = examples.last_mut() {
+                            example.other_files.push((path, source));
+                        }
+                    }
+                    (Some((title, expectation)), path) => {
+                        examples.push(Example {
+                            title,
+                            expectation,
+                            path: path.unwrap_or_else(|| "example.yml".to_owned()),
+                            source,
+                            other_files: Vec::new(),
+                        });
+                        section_has_example = true;
+                    }
+                    (None, _) => (),
+                }
+            } else {
+                source.push_str(line);
+                source.push('\n');
+            }
+        } else if line.trim_start().starts_with("```yaml") {
+            code_block = Some(String::new());
+        } else if let Some(title) = line.strip_prefix("## ") {
+            section_has_example = false;
+            section = if title.starts_with("Not OK") {
+                Some((title, Expectation::Violation))
+            } else if title.starts_with("OK") {
+                Some((title, Expectation::NoViolation))
+            } else {
+                None
+            };
+        } else if line.starts_with("# ") {
+            section = None;
+        }
+    }
+    examples
+}
+
+/// Files with a top-level `runs` key are actions, all others are workflows.
+fn example_kind(source: &str) -> FileKind {
+    if source.lines().any(|line| line.starts_with("runs:")) {
+        FileKind::Action
+    } else {
+        FileKind::Workflow
+    }
+}
+
+/// The configuration of the examples, which enables the policy even if it is disabled by default.
+fn example_config(policy_name: &str) -> Config {
+    Config::from_str(
+        &format!("[policies.{policy_name}]\nenabled = true\n"),
+        EXAMPLE_ROOT,
+    )
+    .unwrap()
+}
+
+/// Returns the violations of the policy in the checked workflow or action of the example, with
+/// the given source.
+///
+/// Examples of policies other than `workflow_schema` must be valid workflows (or actions).
+fn policy_violations(example: &Example, source: &str, policy_name: &str) -> Vec<String> {
+    let title = example.title;
+    let outputs = check_source(
+        source,
+        example_kind(source),
+        &example.path(),
+        &example_config(policy_name),
+        &example.repository(source),
+    )
+    .unwrap_or_else(|err| {
+        panic!("Example \"{title}\" of policy {policy_name} is invalid: {err}\n{source}")
+    });
+    if policy_name != WORKFLOW_SCHEMA {
+        let errors: Vec<_> = outputs
+            .iter()
+            .filter(|output| output.policy().name == WORKFLOW_SCHEMA)
+            .flat_map(|output| output.violations())
+            .map(|violation| violation.message())
+            .collect();
+        assert!(
+            errors.is_empty(),
+            "Example \"{title}\" of policy {policy_name} is invalid: {errors:?}\n{source}"
+        );
+    }
+    outputs
+        .into_iter()
+        .filter(|output| output.policy().name == policy_name)
+        .flat_map(|output| output.into_violations())
+        .map(|violation| violation.message().to_owned())
+        .collect()
+}
+
+/// Checks that the "Not OK" examples in the documentation of the policy violate it and that
+/// the "OK" examples do not.
+///
+/// If fixes are available for the violations of a "Not OK" example, the fixed example must not
+/// violate the policy anymore.
+pub fn check(policy_name: &str) {
+    let policy = get_policies()
+        .find(|policy| policy.name == policy_name)
+        .expect("policy not found");
+    let examples = extract_examples(policy.doc.unwrap_or_default());
+    assert!(
+        examples
+            .iter()
+            .any(|example| example.expectation == Expectation::Violation),
+        "Policy {policy_name} has no \"Not OK\" examples"
+    );
+    assert!(
+        examples
+            .iter()
+            .any(|example| example.expectation == Expectation::NoViolation),
+        "Policy {policy_name} has no \"OK\" examples"
+    );
+
+    for example in examples {
+        let violations = policy_violations(&example, &example.source, policy_name);
+        match example.expectation {
+            Expectation::Violation => assert!(
+                !violations.is_empty(),
+                "Example \"{}\" of policy {policy_name} should violate the policy",
+                example.title
+            ),
+            Expectation::NoViolation => assert!(
+                violations.is_empty(),
+                "Example \"{}\" of policy {policy_name} should not violate the policy, but: {violations:?}",
+                example.title,
+            ),
+        }
+
+        // Invalid workflows (i.e., examples of the `workflow_schema` policy) cannot be fixed.
+        let Ok(fixed_file) = fix_source(
+            example.source.clone(),
+            example_kind(&example.source),
+            &example.path(),
+            &example_config(policy_name),
+            &example.repository(&example.source),
+        ) else {
+            continue;
+        };
+        if fixed_file
+            .fixes
+            .iter()
+            .any(|fix| fix.policy.name == policy_name)
+        {
+            let violations = policy_violations(&example, &fixed_file.fixed_source, policy_name);
+            assert!(
+                violations.is_empty(),
+                "Fixed example \"{}\" of policy {policy_name} should not violate the policy, but: {violations:?}\n{}",
+                example.title,
+                fixed_file.fixed_source
+            );
+        }
+    }
+}
@@ -15,9 +15,24 @@ pub enum GhastlyError {
     /// Represents an IO error.
     #[error(transparent)]
     IOError(#[from] std::io::Error),
-    /// Represents a YAML error.
+    /// Represents a YAML file that could not be parsed.
     #[error(transparent)]
-    YamlError(#[from] marked_yaml::FromYamlError),
+    ParseError(#[from] crate::parser::error::ParseError),
+    /// Represents an error while walking a directory.
+    #[error(transparent)]
+    WalkError(#[from] ignore::Error),
+    /// Represents a syntax error in the configuration file.
+    #[error(transparent)]
+    TomlError(#[from] toml::de::Error),
+    /// Represents a JSON error.
+    #[error(transparent)]
+    JsonError(#[from] serde_json::Error),
+    /// Represents a baseline file with an unsupported version.
+    #[error("unsupported baseline version {0}")]
+    BaselineVersionError(u32),
+    /// Represents an invalid configuration.
+    #[error("invalid configuration: {0}")]
+    ConfigError(String),
 }
 
 pub type GhastlyResult<T> = Result<T, GhastlyError>;
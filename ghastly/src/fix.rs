@@ -0,0 +1,250 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Automatic fixes for policy violations.
+//!
+//! Fixes are text edits of the YAML source (instead of changes to the deserialized workflow), so
+//! that comments and formatting are preserved.
+
+use crate::output::normalize_path;
+use crate::parser::location::offset;
+use crate::Policy;
+use marked_yaml::{Marker, Span};
+use similar::TextDiff;
+use std::ops::Range;
+use std::path::Path;
+
+/// A change of the source text of a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+    span: Span,
+    text: String,
+}
+
+impl Edit {
+    /// Replaces the source text at the given location, which needs to have a start and an end.
+    pub fn replace(span: Span, text: impl Into<String>) -> Self {
+        Self {
+            span,
+            text: text.into(),
+        }
+    }
+
+    /// Inserts text at the given location.
+    pub fn insert(marker: Marker, text: impl Into<String>) -> Self {
+        Self::replace(Span::new_with_marks(marker, marker), text)
+    }
+
+    /// The location of the replaced source text. Insertions start and end at the same location.
+    #[inline]
+    pub const fn span(&self) -> &Span {
+        &self.span
+    }
+
+    /// The text that replaces the source text.
+    #[inline]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The byte range of the replaced source text.
+    fn range(&self, source: &str) -> Option<Range<usize>> {
+        let start = offset(source, self.span.start()?)?;
+        let end = offset(source, self.span.end()?)?;
+        (start <= end).then_some(start..end)
+    }
+}
+
+/// A fix for a policy violation, consisting of one or more edits that are applied together.
+#[derive(Debug, Clone)]
+pub struct Fix {
+    description: String,
+    edits: Vec<Edit>,
+}
+
+impl Fix {
+    pub fn new(description: impl Into<String>, edits: Vec<Edit>) -> Self {
+        Self {
+            description: description.into(),
+            edits,
+        }
+    }
+
+    /// A short description of the fix, e.g. "Replace 'write-all' with '{}'".
+    #[inline]
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    #[inline]
+    pub fn edits(&self) -> &[Edit] {
+        &self.edits
+    }
+}
+
+/// A fix that has been applied to a file.
+#[derive(Debug, Clone)]
+pub struct AppliedFix {
+    /// The policy whose violation has been fixed.
+    pub policy: &'static Policy,
+    /// The description of the fix.
+    pub description: String,
+}
+
+/// The result of fixing a file.
+#[derive(Debug, Clone)]
+pub struct FixedFile {
+    /// The source before applying the fixes.
+    pub source: String,
+    /// The source after applying the fixes.
+    pub fixed_source: String,
+    /// The applied fixes.
+    pub fixes: Vec<AppliedFix>,
+}
+
+impl FixedFile {
+    /// Returns `true` if fixes have been applied.
+    pub fn is_changed(&self) -> bool {
+        self.source != self.fixed_source
+    }
+
+    /// Returns the changes as unified diff (with `a/` and `b/` prefixes, as used by Git).
+    pub fn diff(&self, path: &Path) -> String {
+        let path = normalize_path(path);
+        TextDiff::from_lines(&self.source, &self.fixed_source)
+            .unified_diff()
+            .header(&format!("a/{path}"), &format!("b/{path}"))
+            .to_string()
+    }
+}
+
+/// Applies the fixes to the source.
+///
+/// Fixes are applied in order. A fix is skipped if one of its edits overlaps an edit that is
+/// applied already, or is inserted at the same location (or if it refers to a location outside of
+/// the source), so that conflicting fixes cannot corrupt the file. Edits that only touch each
+/// other do not conflict. Skipped fixes can be applied after the file has been checked
+/// again.
+///
+/// Returns the fixed source and the indices of the applied fixes.
+pub fn apply_fixes<'a>(
+    source: &str,
+    fixes: impl IntoIterator<Item = &'a Fix>,
+) -> (String, Vec<usize>) {
+    let mut edits: Vec<(Range<usize>, &str)> = Vec::new();
+    let mut applied = Vec::new();
+    for (index, fix) in fixes.into_iter().enumerate() {
+        let edit_count = edits.len();
+        for edit in &fix.edits {
+            let Some(range) = edit.range(source) else {
+                break;
+            };
+            if edits.iter().any(|(other, _)| {
+                range == *other || (range.start < other.end && other.start < range.end)
+            }) {
+                break;
+            }
+            edits.push((range, &edit.text));
+        }
+        if edits.len() - edit_count == fix.edits.len() {
+            applied.push(index);
+        } else {
+            edits.truncate(edit_count);
+        }
+    }
+
+    let mut fixed = source.to_owned();
+    // Insertions at the start of a replaced range need to end up in front of the replacement.
+    edits.sort_by_key(|(range, _)| (range.start, range.end));
+    for (range, text) in edits.into_iter().rev() {
+        fixed.replace_range(range, text);
+    }
+    (fixed, applied)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn span(start: (usize, usize), end: (usize, usize)) -> Span {
+        Span::new_with_marks(
+            Marker::new(0, start.0, start.1),
+            Marker::new(0, end.0, end.1),
+        )
+    }
+
+    #[test]
+    fn test_apply_fixes() {
+        let source = "permissions: write-all\njobs: {}\n";
+        let fixes = [
+            Fix::new(
+                "Replace 'write-all' with '{}'",
+                vec![Edit::replace(span((1, 14), (1, 23)), "{}")],
+            ),
+            // Conflicts with the first fix.
+            Fix::new(
+                "Replace 'write' with 'read'",
+                vec![Edit::replace(span((1, 14), (1, 19)), "read")],
+            ),
+            Fix::new(
+                "Add name",
+                vec![
+                    Edit::insert(Marker::new(0, 1, 1), "name: Test\n"),
+                    Edit::insert(Marker::new(0, 3, 1), "on: push\n"),
+                ],
+            ),
+            // Outside of the source.
+            Fix::new("Invalid", vec![Edit::insert(Marker::new(0, 5, 1), "foo")]),
+        ];
+        let (fixed, applied) = apply_fixes(source, &fixes);
+        assert_eq!(fixed, "name: Test\npermissions: {}\njobs: {}\non: push\n");
+        assert_eq!(applied, vec![0, 2]);
+
+        let fixed_file = FixedFile {
+            source: source.to_owned(),
+            fixed_source: fixed,
+            fixes: vec![],
+        };
+        assert_eq!(
+            fixed_file.diff(Path::new("./.github/workflows/ci.yml")),
+            "--- a/.github/workflows/ci.yml
++++ b/.github/workflows/ci.yml
+@@ -1,2 +1,4 @@
+-permissions: write-all
++name: Test
++permissions: {}
+ jobs: {}
++on: push
+"
+        );
+    }
+
+    #[test]
+    fn test_apply_fixes_adjacent_edits() {
+        let source = "run: ${{ a }}${{ b }}\n";
+        let fixes = [
+            Fix::new(
+                "Replace expressions",
+                vec![
+                    Edit::replace(span((1, 6), (1, 14)), "$A"),
+                    Edit::replace(span((1, 14), (1, 22)), "$B"),
+                ],
+            ),
+            // Touches the replaced expressions without overlapping them.
+            Fix::new("Add prefix", vec![Edit::insert(Marker::new(0, 1, 6), "x")]),
+            // Conflicts with the insertion of the previous fix.
+            Fix::new(
+                "Add other prefix",
+                vec![Edit::insert(Marker::new(0, 1, 6), "y")],
+            ),
+        ];
+        let (fixed, applied) = apply_fixes(source, &fixes);
+        assert_eq!(fixed, "run: x$A$B\n");
+        assert_eq!(applied, vec![0, 1]);
+    }
+}
@@ -6,22 +6,254 @@
 //
 // SPDX-License-Identifier: MPL-2.0
 
-use std::fs::File;
+use std::collections::HashSet;
+use std::fs;
 use std::path::Path;
 
+mod baseline;
+mod config;
+mod discovery;
+#[cfg(test)]
+mod doc_examples;
 mod error;
+mod fix;
+mod output;
 mod parser;
 mod policies;
+mod repository;
+mod suppression;
 
+pub use baseline::Baseline;
+pub use config::{Config, PolicyConfig, CONFIG_FILE_NAME};
+pub use discovery::{discover_files, FileKind};
 pub use error::GhastlyError as Error;
 pub use error::GhastlyResult as Result;
-use parser::workflow::Workflow;
-pub use policies::{get_policies, Policy, PolicyCheckOutput};
-
-pub fn check_workflow(path: impl AsRef<Path>) -> Result<Vec<PolicyCheckOutput<'static>>> {
-    let mut file = File::open(path)?;
-    let workflow = Workflow::from_reader(&mut file)?;
-    Ok(get_policies()
-        .map(|policy| policy.check(&workflow))
-        .collect())
+pub use fix::{apply_fixes, AppliedFix, Edit, Fix, FixedFile};
+pub use output::{Finding, OutputFormat};
+use parser::document::Document;
+pub use policies::{
+    get_policies, Confidence, KnownAction, Policy, PolicyCheckOutput, PolicyContext, PolicyOptions,
+    PolicyViolation, Severity,
+};
+use policies::{parse_error_violations, unused_suppression, UNUSED_SUPPRESSION, WORKFLOW_SCHEMA};
+pub use repository::{CallSecret, Repository, WorkflowCall, MAX_CALL_DEPTH};
+use suppression::parse_suppressions;
+
+/// The maximum number of times that a file is checked and fixed. Fixes that conflict with other
+/// fixes are skipped, but may be applied after the file has been checked again.
+const MAX_FIX_PASSES: usize = 10;
+
+/// Checks the policy with the configuration for the given file, or returns `None` if the policy
+/// is disabled.
+fn check_policy(
+    policy: &'static Policy,
+    document: &Document,
+    path: &Path,
+    config: &Config,
+    repository: &Repository,
+) -> Option<PolicyCheckOutput<'static>> {
+    report_policy(policy, path, config, |options| {
+        policy.check(document, &PolicyContext::new(path, options, repository))
+    })
+}
+
+/// Reports the output of the check function with the configuration of the policy for the given
+/// file, or returns `None` if the policy is disabled (or the check function returns `None`).
+fn report_policy<F>(
+    policy: &'static Policy,
+    path: &Path,
+    config: &Config,
+    check_fn: F,
+) -> Option<PolicyCheckOutput<'static>>
+where
+    F: FnOnce(&PolicyOptions) -> Option<PolicyCheckOutput<'static>>,
+{
+    let policy_config = config.policy_config(policy.name, path);
+    if !policy_config.is_enabled(policy) {
+        return None;
+    }
+    let output = check_fn(&policy_config.options())?;
+    Some(match policy_config.severity() {
+        Some(severity) => output.with_severity(severity),
+        None => output,
+    })
+}
+
+pub fn check_workflow(
+    path: impl AsRef<Path>,
+    config: &Config,
+) -> Result<Vec<PolicyCheckOutput<'static>>> {
+    check_file(path, FileKind::Workflow, config, &Repository::default())
+}
+
+/// Checks all enabled policies on the source of a workflow or action.
+///
+/// If a workflow cannot be parsed, the error is reported as violation of the `workflow_schema`
+/// policy, or returned if that policy is disabled.
+fn check_source(
+    source: &str,
+    kind: FileKind,
+    path: &Path,
+    config: &Config,
+    repository: &Repository,
+) -> Result<Vec<PolicyCheckOutput<'static>>> {
+    let err = match Document::from_reader(&mut source.as_bytes(), kind) {
+        Ok(document) => return Ok(check_document(&document, path, config, repository)),
+        Err(Error::ParseError(err)) if kind == FileKind::Workflow => err,
+        Err(err) => return Err(err),
+    };
+    let Some(output) = get_policies()
+        .find(|policy| policy.name == WORKFLOW_SCHEMA)
+        .and_then(|policy| {
+            report_policy(policy, path, config, |_options| {
+                Some(PolicyCheckOutput::new(
+                    policy,
+                    parse_error_violations(source, &err),
+                ))
+            })
+        })
+    else {
+        return Err(err.into());
+    };
+    Ok(apply_suppressions(source, path, config, vec![output]))
+}
+
+/// Checks all enabled policies on the document and applies suppression comments.
+///
+/// Policies that are reported by the engine are not checked here.
+fn check_document(
+    document: &Document,
+    path: &Path,
+    config: &Config,
+    repository: &Repository,
+) -> Vec<PolicyCheckOutput<'static>> {
+    let outputs: Vec<_> = get_policies()
+        .filter_map(|policy| check_policy(policy, document, path, config, repository))
+        .collect();
+    apply_suppressions(document.source().as_str(), path, config, outputs)
+}
+
+/// Removes violations that are suppressed by comments in the source and reports unused
+/// suppressions.
+fn apply_suppressions(
+    source: &str,
+    path: &Path,
+    config: &Config,
+    mut outputs: Vec<PolicyCheckOutput<'static>>,
+) -> Vec<PolicyCheckOutput<'static>> {
+    // Remove suppressed violations and remember which suppressions were used.
+    let suppressions = parse_suppressions(source);
+    let mut used_suppressions = HashSet::new();
+    outputs.iter_mut().for_each(|output| {
+        let policy_name = output.policy().name;
+        output.retain_violations(|violation| {
+            let suppressed_by: Vec<_> = suppressions
+                .iter()
+                .enumerate()
+                .filter(|(_index, suppression)| suppression.suppresses(policy_name, violation))
+                .map(|(index, _suppression)| (index, policy_name))
+                .collect();
+            let is_suppressed = !suppressed_by.is_empty();
+            used_suppressions.extend(suppressed_by);
+            !is_suppressed
+        });
+    });
+
+    if let Some(output) = get_policies()
+        .find(|policy| policy.name == UNUSED_SUPPRESSION)
+        .and_then(|policy| {
+            report_policy(policy, path, config, |_options| {
+                let violations = unused_suppression(&suppressions, &used_suppressions, &outputs);
+                Some(PolicyCheckOutput::new(policy, violations))
+            })
+        })
+    {
+        outputs.push(output);
+    }
+
+    outputs
+}
+
+/// Checks all enabled policies on the file.
+///
+/// Policies may look up other files in the repository. If the file itself is part of the
+/// repository, it is not read again.
+pub fn check_file(
+    path: impl AsRef<Path>,
+    kind: FileKind,
+    config: &Config,
+    repository: &Repository,
+) -> Result<Vec<PolicyCheckOutput<'static>>> {
+    let path = path.as_ref();
+    if let Some(document) = repository
+        .document(path)
+        .filter(|document| document.kind() == kind)
+    {
+        return Ok(check_document(document, path, config, repository));
+    }
+    let source = fs::read_to_string(path)?;
+    check_source(&source, kind, path, config, repository)
+}
+
+/// Applies the fixes for violations of all enabled policies to the source of a workflow or
+/// action.
+///
+/// Fixes are applied until no more fixes are available. If applying a fix would result in an
+/// invalid document, the fixes of that pass are discarded.
+fn fix_source(
+    source: String,
+    kind: FileKind,
+    path: &Path,
+    config: &Config,
+    repository: &Repository,
+) -> Result<FixedFile> {
+    let mut document = Document::from_reader(&mut source.as_bytes(), kind)?;
+    let mut fixed_source = source.clone();
+    let mut applied_fixes = Vec::new();
+    for _ in 0..MAX_FIX_PASSES {
+        let outputs = check_document(&document, path, config, repository);
+        let fixes: Vec<_> = outputs
+            .iter()
+            .flat_map(|output| {
+                output
+                    .violations()
+                    .iter()
+                    .filter_map(|violation| violation.fix())
+                    .map(|fix| (output.policy(), fix))
+            })
+            .collect();
+        let (new_source, applied) = apply_fixes(&fixed_source, fixes.iter().map(|(_, fix)| *fix));
+        if applied.is_empty() {
+            break;
+        }
+        let Ok(new_document) = Document::from_reader(&mut new_source.as_bytes(), kind) else {
+            break;
+        };
+        applied_fixes.extend(applied.into_iter().map(|index| AppliedFix {
+            policy: fixes[index].0,
+            description: fixes[index].1.description().to_owned(),
+        }));
+        fixed_source = new_source;
+        document = new_document;
+    }
+
+    Ok(FixedFile {
+        source,
+        fixed_source,
+        fixes: applied_fixes,
+    })
+}
+
+/// Applies the fixes for violations of all enabled policies to the file.
+///
+/// The file itself is not modified.
+pub fn fix_file(
+    path: impl AsRef<Path>,
+    kind: FileKind,
+    config: &Config,
+    repository: &Repository,
+) -> Result<FixedFile> {
+    let path = path.as_ref();
+    let source = fs::read_to_string(path)?;
+    fix_source(source, kind, path, config, repository)
 }
@@ -7,7 +7,12 @@
 // SPDX-License-Identifier: MPL-2.0
 
 use clap::{Parser, Subcommand};
-use std::path::PathBuf;
+use ghastly::{Config, OutputFormat, Repository, Severity};
+use std::collections::btree_map::{BTreeMap, Entry};
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 use termimad::{Alignment, MadSkin};
 
 #[derive(Parser)]
@@ -18,13 +23,67 @@ struct Args {
     command: Commands,
 }
 
+/// Exit code if no violations (at or above the `--fail-on` severity) were found.
+const EXIT_SUCCESS: u8 = 0;
+/// Exit code if violations at or above the `--fail-on` severity were found.
+const EXIT_VIOLATIONS: u8 = 1;
+/// Exit code if a file could not be read or parsed (or the command line was invalid).
+const EXIT_ERROR: u8 = 2;
+
 #[derive(Subcommand)]
 enum Commands {
-    /// Check workflow file.
+    /// Check workflow files.
+    ///
+    /// Directories are searched recursively for workflows (`.github/workflows/*.yml`) and action
+    /// metadata files (`action.yml`), skipping files ignored by `.gitignore`.
+    #[command(after_help = "Exit codes:
+  0  No violations at or above the --fail-on severity were found.
+  1  Violations at or above the --fail-on severity were found.
+  2  A file could not be read or parsed, or the command line was invalid.")]
     Check {
-        /// Workflow file to check.
-        #[arg(value_name = "FILE")]
-        path: PathBuf,
+        /// Files or directories to check.
+        #[arg(value_name = "PATH", default_value = ".")]
+        paths: Vec<PathBuf>,
+        /// Exit with a non-zero code if violations with at least this severity were found
+        /// (info, low, medium or high).
+        #[arg(long, value_name = "SEVERITY", default_value_t = Severity::Low)]
+        fail_on: Severity,
+        /// Configuration file (default: `.ghastly.toml` in the root directory of the Git
+        /// repository that contains the checked file).
+        #[arg(long, value_name = "FILE")]
+        config: Option<PathBuf>,
+        /// Only report violations that are not contained in this baseline file.
+        #[arg(long, value_name = "FILE", conflicts_with = "write_baseline")]
+        baseline: Option<PathBuf>,
+        /// Write all violations to this baseline file instead of reporting them.
+        #[arg(long, value_name = "FILE")]
+        write_baseline: Option<PathBuf>,
+        /// Output format (pretty, text, sarif, json, jsonl or github). Defaults to pretty if the
+        /// output is a terminal and text otherwise.
+        #[arg(long, value_name = "FORMAT")]
+        format: Option<OutputFormat>,
+    },
+    /// Fix violations automatically, where possible.
+    ///
+    /// Fixes are text edits that preserve comments and formatting. Violations of disabled or
+    /// suppressed policies are not fixed.
+    #[command(after_help = "Exit codes:
+  0  All files were fixed (or no fixes were necessary).
+  2  A file could not be read, parsed or written, or the command line was invalid.")]
+    Fix {
+        /// Files or directories to fix.
+        #[arg(value_name = "PATH", default_value = ".")]
+        paths: Vec<PathBuf>,
+        /// Configuration file (default: `.ghastly.toml` in the root directory of the Git
+        /// repository that contains the fixed file).
+        #[arg(long, value_name = "FILE")]
+        config: Option<PathBuf>,
+        /// Only print the fixes instead of modifying the files.
+        #[arg(long)]
+        dry_run: bool,
+        /// Print a unified diff of the fixes instead of modifying the files.
+        #[arg(long)]
+        diff: bool,
     },
     /// List policies.
     List,
@@ -35,55 +94,265 @@ enum Commands {
     },
 }
 
-fn main() -> ghastly::Result<()> {
+/// The configuration and the workflows and actions of the repositories of the checked files.
+struct Repositories {
+    /// The configuration file given on the command line, which applies to all repositories.
+    config: Option<Config>,
+    /// The discovered configuration and the files of each repository, by root directory.
+    repositories: BTreeMap<PathBuf, (Config, Repository)>,
+}
+
+impl Repositories {
+    /// Loads the configuration file (if given).
+    fn new(config: Option<&Path>) -> ghastly::Result<Self> {
+        Ok(Self {
+            config: config.map(Config::from_path).transpose()?,
+            repositories: BTreeMap::new(),
+        })
+    }
+
+    /// The configuration and the repository of the file.
+    ///
+    /// All files of a repository are loaded before the first of its files is checked, so that
+    /// policies can look up other files (e.g. local actions) while a file is checked. Unless a
+    /// configuration file was given, it is discovered in the root directory of the repository,
+    /// which the globs of its overrides are relative to.
+    fn get(&mut self, path: &Path) -> ghastly::Result<(&Config, &Repository)> {
+        let (config, repository) = match self.repositories.entry(Repository::find_root(path)) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let config = match self.config {
+                    Some(_) => Config::default(),
+                    None => Config::discover(entry.key())?.unwrap_or_default(),
+                };
+                let repository = Repository::discover(entry.key());
+                entry.insert((config, repository))
+            }
+        };
+        Ok((self.config.as_ref().unwrap_or(config), repository))
+    }
+}
+
+fn main() -> ExitCode {
     let args = Args::parse();
 
     match &args.command {
-        Commands::Check { path } => {
-            let output = ghastly::check_workflow(path)?;
-            let mut policy_violations: Vec<_> = output
-                .iter()
-                .flat_map(|policy_output| {
-                    policy_output
-                        .violations()
-                        .iter()
-                        .map(|violation| (policy_output.policy(), violation))
-                })
-                .collect();
-            policy_violations.sort_by_key(|(_policy, violation)| {
-                violation
-                    .source()
-                    .start()
-                    .map(|marker| (marker.line(), marker.column()))
-                    .unwrap_or_default()
+        Commands::Check {
+            paths,
+            fail_on,
+            config,
+            baseline,
+            write_baseline,
+            format,
+        } => {
+            let mut repositories = match Repositories::new(config.as_deref()) {
+                Ok(repositories) => repositories,
+                Err(err) => {
+                    eprintln!("error: {err}");
+                    return ExitCode::from(EXIT_ERROR);
+                }
+            };
+            let baseline = match baseline.as_ref().map(ghastly::Baseline::from_path) {
+                Some(Ok(baseline)) => Some(baseline),
+                Some(Err(err)) => {
+                    eprintln!("error: {err}");
+                    return ExitCode::from(EXIT_ERROR);
+                }
+                None => None,
+            };
+            let mut file_count = 0;
+            let mut error_count = 0;
+            let mut findings = Vec::new();
+            for file in ghastly::discover_files(paths) {
+                let (path, kind) = match file {
+                    Ok(file) => file,
+                    Err(err) => {
+                        eprintln!("error: {err}");
+                        error_count += 1;
+                        continue;
+                    }
+                };
+                let (config, repository) = match repositories.get(&path) {
+                    Ok(found) => found,
+                    Err(err) => {
+                        eprintln!("error: {err}");
+                        return ExitCode::from(EXIT_ERROR);
+                    }
+                };
+                let output = match ghastly::check_file(&path, kind, config, repository) {
+                    Ok(output) => output,
+                    Err(err) => {
+                        eprintln!("{}: error: {err}", path.display());
+                        error_count += 1;
+                        continue;
+                    }
+                };
+                file_count += 1;
+                findings.extend(ghastly::Finding::from_outputs(&path, repository, &output));
+            }
+            if file_count == 0 && error_count == 0 {
+                // Most likely, the wrong directory was checked.
+                eprintln!("warning: no workflow or action metadata files found");
+            }
+
+            if let Some(path) = write_baseline {
+                let baseline = ghastly::Baseline::from_findings(&findings);
+                if let Err(err) = baseline.write(path) {
+                    eprintln!("error: {err}");
+                    return ExitCode::from(EXIT_ERROR);
+                }
+                eprintln!(
+                    "Checked {file_count} file(s), wrote {} violation(s) to {} and found {error_count} error(s).",
+                    baseline.len(),
+                    path.display()
+                );
+                return if error_count > 0 {
+                    ExitCode::from(EXIT_ERROR)
+                } else {
+                    ExitCode::from(EXIT_SUCCESS)
+                };
+            }
+
+            let mut baseline_count = 0;
+            if let Some(baseline) = baseline {
+                let count = findings.len();
+                findings = baseline.filter(findings);
+                baseline_count = count - findings.len();
+            }
+
+            let format = format.unwrap_or_else(|| {
+                if io::stdout().is_terminal() {
+                    OutputFormat::Pretty
+                } else {
+                    OutputFormat::Text
+                }
             });
-            policy_violations
-                .into_iter()
-                .for_each(|(policy, violation)| {
-                    let line = violation
-                        .source()
-                        .start()
-                        .map(|marker| marker.line())
-                        .unwrap_or_default();
-                    let column = violation
-                        .source()
-                        .start()
-                        .map(|marker| marker.column())
-                        .unwrap_or_default();
-                    println!(
-                        "{path}:{line}:{column}:{message} ({policy_name})",
-                        path = path.display(),
-                        message = violation.message(),
-                        policy_name = policy.name
-                    );
-                });
-            Ok(())
+            if let Err(err) = format.write(&mut io::stdout().lock(), &findings) {
+                eprintln!("error: {err}");
+                return ExitCode::from(EXIT_ERROR);
+            }
+            let violation_count = findings.len();
+            let failing_violation_count = findings
+                .iter()
+                .filter(|finding| finding.severity() >= *fail_on)
+                .count();
+            if baseline_count > 0 {
+                eprintln!(
+                    "Checked {file_count} file(s), found {violation_count} new violation(s) ({baseline_count} in baseline) and {error_count} error(s)."
+                );
+            } else {
+                eprintln!(
+                    "Checked {file_count} file(s), found {violation_count} violation(s) and {error_count} error(s)."
+                );
+            }
+            if error_count > 0 {
+                ExitCode::from(EXIT_ERROR)
+            } else if failing_violation_count > 0 {
+                ExitCode::from(EXIT_VIOLATIONS)
+            } else {
+                ExitCode::from(EXIT_SUCCESS)
+            }
+        }
+        Commands::Fix {
+            paths,
+            config,
+            dry_run,
+            diff,
+        } => {
+            // Printing a diff never modifies the files.
+            let dry_run = *dry_run || *diff;
+            let mut repositories = match Repositories::new(config.as_deref()) {
+                Ok(repositories) => repositories,
+                Err(err) => {
+                    eprintln!("error: {err}");
+                    return ExitCode::from(EXIT_ERROR);
+                }
+            };
+            let mut fix_count = 0;
+            let mut fixed_file_count = 0;
+            let mut error_count = 0;
+            for file in ghastly::discover_files(paths) {
+                let (path, kind) = match file {
+                    Ok(file) => file,
+                    Err(err) => {
+                        eprintln!("error: {err}");
+                        error_count += 1;
+                        continue;
+                    }
+                };
+                let (config, repository) = match repositories.get(&path) {
+                    Ok(found) => found,
+                    Err(err) => {
+                        eprintln!("error: {err}");
+                        return ExitCode::from(EXIT_ERROR);
+                    }
+                };
+                let fixed_file = match ghastly::fix_file(&path, kind, config, repository) {
+                    Ok(fixed_file) => fixed_file,
+                    Err(err) => {
+                        eprintln!("{}: error: {err}", path.display());
+                        error_count += 1;
+                        continue;
+                    }
+                };
+                if !fixed_file.is_changed() {
+                    continue;
+                }
+                if !dry_run {
+                    if let Err(err) = fs::write(&path, &fixed_file.fixed_source) {
+                        eprintln!("{}: error: {err}", path.display());
+                        error_count += 1;
+                        continue;
+                    }
+                }
+                if *diff {
+                    print!("{}", fixed_file.diff(&path));
+                } else {
+                    for fix in &fixed_file.fixes {
+                        println!(
+                            "{}: {} ({})",
+                            path.display(),
+                            fix.description,
+                            fix.policy.name
+                        );
+                    }
+                }
+                fix_count += fixed_file.fixes.len();
+                fixed_file_count += 1;
+            }
+
+            let verb = if dry_run { "Would apply" } else { "Applied" };
+            eprintln!(
+                "{verb} {fix_count} fix(es) in {fixed_file_count} file(s) and found {error_count} error(s)."
+            );
+            if error_count > 0 {
+                ExitCode::from(EXIT_ERROR)
+            } else {
+                ExitCode::from(EXIT_SUCCESS)
+            }
         }
         Commands::List => {
-            ghastly::get_policies().for_each(|policy| {
-                println!("{}", policy.name);
+            let mut policies: Vec<_> = ghastly::get_policies().collect();
+            policies.sort_by_key(|policy| policy.name);
+            let name_width = policies
+                .iter()
+                .map(|policy| policy.name.len())
+                .max()
+                .unwrap_or_default();
+            println!(
+                "{:name_width$}  {:8}  {:10}  CATEGORY",
+                "POLICY", "SEVERITY", "CONFIDENCE"
+            );
+            policies.into_iter().for_each(|policy| {
+                println!(
+                    "{name:name_width$}  {severity:8}  {confidence:10}  {category}",
+                    name = policy.name,
+                    severity = policy.severity.as_str(),
+                    confidence = policy.confidence.as_str(),
+                    category = policy.category.unwrap_or("-"),
+                );
             });
-            Ok(())
+            ExitCode::from(EXIT_SUCCESS)
         }
         Commands::Show { name } => {
             if let Some(policy) = ghastly::get_policies().find(|policy| policy.name == name) {
@@ -93,14 +362,30 @@ fn main() -> ghastly::Result<()> {
                         line_style.align = Alignment::Left;
                     });
 
+                    let mut metadata = format!(
+                        "- **Severity:** {}\n- **Confidence:** {}\n",
+                        policy.severity, policy.confidence
+                    );
+                    if let Some(category) = policy.category {
+                        metadata.push_str(&format!("- **Category:** {category}\n"));
+                    }
+                    if !policy.tags.is_empty() {
+                        metadata.push_str(&format!("- **Tags:** {}\n", policy.tags.join(", ")));
+                    }
+                    if !policy.enabled {
+                        metadata.push_str("- **Enabled by default:** no\n");
+                    }
+                    skin.print_text(&metadata);
+                    println!();
                     skin.print_text(doc);
                 } else {
                     eprintln!("Policy {} has not documentation", policy.name);
                 }
             } else {
                 eprintln!("Policy {} not found", name);
+                return ExitCode::from(EXIT_ERROR);
             };
-            Ok(())
+            ExitCode::from(EXIT_SUCCESS)
         }
     }
 }
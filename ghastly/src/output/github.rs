@@ -0,0 +1,105 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! GitHub Actions workflow commands, which show findings as annotations on pull request diffs.
+//!
+//! Documentation: <https://docs.github.com/en/actions/writing-workflows/choosing-what-your-workflow-does/workflow-commands-for-github-actions#setting-an-error-message>
+
+use super::{normalize_path, Finding};
+use crate::policies::Severity;
+use std::io::{self, Write};
+
+/// The workflow command for a severity.
+const fn command(severity: Severity) -> &'static str {
+    match severity {
+        Severity::High => "error",
+        Severity::Medium => "warning",
+        Severity::Low | Severity::Info => "notice",
+    }
+}
+
+/// Escapes the message of a workflow command.
+fn escape_data(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escapes a property value of a workflow command.
+fn escape_property(value: &str) -> String {
+    escape_data(value).replace(':', "%3A").replace(',', "%2C")
+}
+
+/// Writes one workflow command per finding, e.g.
+/// `::error file=.github/workflows/build.yml,line=1,col=2,endLine=1,endColumn=5,title=policy::message`.
+///
+/// The file is given relative to the repository root, which GitHub resolves it against. High
+/// severity findings are reported as errors, medium severity findings as warnings and all
+/// other findings as notices.
+pub fn write<W>(writer: &mut W, findings: &[Finding]) -> io::Result<()>
+where
+    W: Write,
+{
+    findings.iter().try_for_each(|finding| {
+        let (line, column) = finding.start();
+        let (end_line, mut end_column) = finding.end();
+        // The end of the span is exclusive, but the end column of an annotation is inclusive (and
+        // only used if the annotation starts and ends on the same line).
+        if end_line == line {
+            end_column = end_column.saturating_sub(1).max(column);
+        }
+        writeln!(
+            writer,
+            "::{command} file={file},line={line},col={column},endLine={end_line},endColumn={end_column},title={title}::{message}",
+            command = command(finding.severity()),
+            file = escape_property(&normalize_path(finding.repository_path())),
+            title = escape_property(finding.policy().name),
+            message = escape_data(finding.violation().message()),
+        )
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape_data("100%\nfoo: bar"), "100%25%0Afoo: bar");
+        assert_eq!(escape_property("a:b,c"), "a%3Ab%2Cc");
+    }
+
+    #[test]
+    fn test_write() {
+        let source = "on: push\njobs:\n  build:\n    runs-on: ubuntu-latest\n    permissions: write-all\n    steps:\n      - run: echo\n";
+        let findings = super::super::check_workflow_findings(source, "no_all_permissions");
+        let mut output = Vec::new();
+        write(&mut output, &findings).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "::warning file=build.yml,line=5,col=18,endLine=5,endColumn=26,title=no_all_permissions::Job build should not use the 'write-all' permission.\n"
+        );
+
+        // A file checked from a subdirectory of the repository is still relative to its root.
+        let findings = super::super::check_workflow_findings_at(
+            source,
+            Path::new("workflows/build.yml"),
+            Path::new(".."),
+            "no_all_permissions",
+        );
+        let mut output = Vec::new();
+        write(&mut output, &findings).unwrap();
+        let current_dir = std::env::current_dir().unwrap();
+        let subdirectory = current_dir.file_name().unwrap().to_str().unwrap();
+        assert!(String::from_utf8(output).unwrap().starts_with(&format!(
+            "::warning file={subdirectory}/workflows/build.yml,"
+        )));
+    }
+}
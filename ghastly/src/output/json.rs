@@ -0,0 +1,203 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! JSON and JSON Lines output.
+//!
+//! Each finding is serialized as an object with the following fields:
+//!
+//! - `file`: The path of the file (with `/` as separator).
+//! - `start_line`, `start_column`, `end_line`, `end_column`: The location (1-based).
+//! - `policy`: The name of the violated policy.
+//! - `job`: The ID of the job (or `null`).
+//! - `step`: The `id` or the 1-based index of the step (or `null`).
+//! - `severity`: The severity (`info`, `low`, `medium` or `high`).
+//! - `confidence`: The confidence of the policy (`low`, `medium` or `high`).
+//! - `category`: The category of the policy (or `null`).
+//! - `tags`: The tags of the policy.
+//! - `message`: The message.
+//! - `fingerprint`: A stable identifier (see [`Finding::fingerprint`]).
+//! - `fix`: The fix that `ghastly fix` would apply (or `null`), with a `description` and a list
+//!   of `edits`. Each edit replaces the text between its `start_line`/`start_column` and its
+//!   `end_line`/`end_column` (exclusive) with `text`. Edits of insertions start and end at the
+//!   same location.
+
+use super::{normalize_path, Finding};
+use crate::fix::{Edit, Fix};
+use serde::Serialize;
+use std::io::{self, Write};
+
+#[derive(Serialize)]
+struct JsonEdit<'a> {
+    start_line: usize,
+    start_column: usize,
+    end_line: usize,
+    end_column: usize,
+    text: &'a str,
+}
+
+impl<'a> From<&'a Edit> for JsonEdit<'a> {
+    fn from(edit: &'a Edit) -> Self {
+        let start = edit.span().start();
+        let end = edit.span().end().or(start);
+        Self {
+            start_line: start.map(|marker| marker.line()).unwrap_or_default(),
+            start_column: start.map(|marker| marker.column()).unwrap_or_default(),
+            end_line: end.map(|marker| marker.line()).unwrap_or_default(),
+            end_column: end.map(|marker| marker.column()).unwrap_or_default(),
+            text: edit.text(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonFix<'a> {
+    description: &'a str,
+    edits: Vec<JsonEdit<'a>>,
+}
+
+impl<'a> From<&'a Fix> for JsonFix<'a> {
+    fn from(fix: &'a Fix) -> Self {
+        Self {
+            description: fix.description(),
+            edits: fix.edits().iter().map(JsonEdit::from).collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonFinding<'a> {
+    file: String,
+    start_line: usize,
+    start_column: usize,
+    end_line: usize,
+    end_column: usize,
+    policy: &'a str,
+    job: Option<&'a str>,
+    step: Option<&'a str>,
+    severity: &'a str,
+    confidence: &'a str,
+    category: Option<&'a str>,
+    tags: &'a [&'a str],
+    message: &'a str,
+    fingerprint: String,
+    fix: Option<JsonFix<'a>>,
+}
+
+impl<'a> From<&'a Finding> for JsonFinding<'a> {
+    fn from(finding: &'a Finding) -> Self {
+        let (start_line, start_column) = finding.start();
+        let (end_line, end_column) = finding.end();
+        Self {
+            file: normalize_path(finding.path()),
+            start_line,
+            start_column,
+            end_line,
+            end_column,
+            policy: finding.policy().name,
+            job: finding.violation().job(),
+            step: finding.violation().step(),
+            severity: finding.severity().as_str(),
+            confidence: finding.policy().confidence.as_str(),
+            category: finding.policy().category,
+            tags: finding.policy().tags,
+            message: finding.violation().message(),
+            fingerprint: finding.fingerprint(),
+            fix: finding.violation().fix().map(JsonFix::from),
+        }
+    }
+}
+
+/// Writes all findings as a single JSON array.
+pub fn write<W>(writer: &mut W, findings: &[Finding]) -> io::Result<()>
+where
+    W: Write,
+{
+    let findings: Vec<_> = findings.iter().map(JsonFinding::from).collect();
+    serde_json::to_writer_pretty(&mut *writer, &findings)?;
+    writeln!(writer)
+}
+
+/// Writes one JSON object per finding and line.
+pub fn write_lines<W>(writer: &mut W, findings: &[Finding]) -> io::Result<()>
+where
+    W: Write,
+{
+    findings.iter().try_for_each(|finding| {
+        serde_json::to_writer(&mut *writer, &JsonFinding::from(finding))?;
+        writeln!(writer)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::{json, Value};
+
+    const SOURCE: &str = "on: push\njobs:\n  build:\n    runs-on: ubuntu-latest\n    permissions: write-all\n    steps:\n      - run: echo\n  test:\n    runs-on: ubuntu-latest\n    permissions: read-all\n    steps:\n      - run: echo\n";
+
+    #[test]
+    fn test_write() {
+        let findings = super::super::check_workflow_findings(SOURCE, "no_all_permissions");
+        let mut output = Vec::new();
+        write(&mut output, &findings).unwrap();
+        let value: Value = serde_json::from_slice(&output).unwrap();
+        let array = value.as_array().unwrap();
+        assert_eq!(array.len(), 2);
+        assert_eq!(
+            array[0],
+            json!({
+                "file": "build.yml",
+                "start_line": 5,
+                "start_column": 18,
+                "end_line": 5,
+                "end_column": 27,
+                "policy": "no_all_permissions",
+                "job": "build",
+                "step": null,
+                "severity": findings[0].severity().as_str(),
+                "confidence": findings[0].policy().confidence.as_str(),
+                "category": findings[0].policy().category,
+                "tags": findings[0].policy().tags,
+                "message": "Job build should not use the 'write-all' permission.",
+                "fingerprint": findings[0].fingerprint(),
+                "fix": {
+                    "description": "Replace 'write-all' with '{}'",
+                    "edits": [{
+                        "start_line": 5,
+                        "start_column": 18,
+                        "end_line": 5,
+                        "end_column": 27,
+                        "text": "{}",
+                    }],
+                },
+            })
+        );
+        assert_eq!(array[1]["job"], "test");
+        assert_eq!(array[1]["fingerprint"], findings[1].fingerprint());
+    }
+
+    #[test]
+    fn test_write_lines() {
+        let findings = super::super::check_workflow_findings(SOURCE, "no_all_permissions");
+        let mut output = Vec::new();
+        write_lines(&mut output, &findings).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), findings.len());
+        for (line, finding) in lines.iter().zip(&findings) {
+            assert_eq!(line["policy"], "no_all_permissions");
+            assert_eq!(line["job"], finding.violation().job().unwrap());
+            assert_eq!(line["fingerprint"], finding.fingerprint());
+        }
+        assert_eq!(lines[1]["start_line"], 10);
+        assert_ne!(lines[0]["fingerprint"], lines[1]["fingerprint"]);
+    }
+}
@@ -0,0 +1,284 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::config::absolute_path;
+use crate::policies::{Policy, PolicyCheckOutput, PolicyViolation, Severity};
+use crate::Repository;
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::io::{self, Write};
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+
+mod github;
+mod json;
+mod pretty;
+mod sarif;
+mod text;
+
+/// Converts a path into a string with `/` as separator and without leading `./`, so that it is
+/// the same on all platforms.
+pub(crate) fn normalize_path(path: &Path) -> String {
+    path.components()
+        .filter(|component| !matches!(component, Component::CurDir))
+        .map(|component| match component {
+            Component::RootDir => "".into(),
+            component => component.as_os_str().to_string_lossy(),
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// A policy violation found in a file.
+#[derive(Debug, Clone)]
+pub struct Finding {
+    path: PathBuf,
+    repository_path: PathBuf,
+    policy: &'static Policy,
+    violation: PolicyViolation,
+    severity: Severity,
+}
+
+impl Finding {
+    /// Creates findings from the output of all policies checked on a file of the repository,
+    /// sorted by their position in the file.
+    pub fn from_outputs(
+        path: &Path,
+        repository: &Repository,
+        outputs: &[PolicyCheckOutput<'static>],
+    ) -> Vec<Finding> {
+        let absolute_path = absolute_path(path);
+        let repository_path = absolute_path
+            .strip_prefix(repository.root())
+            .map_or_else(|_| absolute_path.clone(), Path::to_owned);
+        let mut findings: Vec<_> = outputs
+            .iter()
+            .flat_map(|output| {
+                output.violations().iter().map(|violation| Finding {
+                    path: path.to_owned(),
+                    repository_path: repository_path.clone(),
+                    policy: output.policy(),
+                    violation: violation.clone(),
+                    severity: output.severity_of(violation),
+                })
+            })
+            .collect();
+        findings.sort_by_key(Finding::start);
+        findings
+    }
+
+    /// The path of the file that contains the violation.
+    #[inline]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The path of the file relative to the root of its repository, which does not depend on the
+    /// working directory or on how the path was passed on the command line.
+    #[inline]
+    pub fn repository_path(&self) -> &Path {
+        &self.repository_path
+    }
+
+    /// The policy that was violated.
+    #[inline]
+    pub const fn policy(&self) -> &'static Policy {
+        self.policy
+    }
+
+    /// The violation.
+    #[inline]
+    pub const fn violation(&self) -> &PolicyViolation {
+        &self.violation
+    }
+
+    /// The effective severity of the violation.
+    #[inline]
+    pub const fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// A stable identifier of this finding.
+    ///
+    /// The fingerprint is derived from the policy name, the path in the repository, the job and step
+    /// (if any) and the key of the violation (see [`PolicyViolation::key`]), but not from the
+    /// position, so that it does not change if unrelated lines are added or removed. Steps are
+    /// identified by their `id` (if set), so adding or reordering other steps does not change the
+    /// fingerprint either.
+    pub fn fingerprint(&self) -> String {
+        let mut hasher = Sha256::new();
+        [
+            self.policy.name,
+            &normalize_path(&self.repository_path),
+            self.violation.job().unwrap_or_default(),
+            self.violation.step().unwrap_or_default(),
+            self.violation.key(),
+        ]
+        .into_iter()
+        .for_each(|part| {
+            hasher.update(part.as_bytes());
+            hasher.update([0]);
+        });
+        hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+
+    /// The line and column where the violation starts (or `(0, 0)` if unknown).
+    pub fn start(&self) -> (usize, usize) {
+        self.violation
+            .source()
+            .start()
+            .map(|marker| (marker.line(), marker.column()))
+            .unwrap_or_default()
+    }
+
+    /// The line and column where the violation ends (or the start if unknown).
+    pub fn end(&self) -> (usize, usize) {
+        self.violation
+            .source()
+            .end()
+            .map(|marker| (marker.line(), marker.column()))
+            .unwrap_or_else(|| self.start())
+    }
+}
+
+/// The format in which findings are written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// One line per finding in the form `path:line:column:message (policy, severity)`.
+    #[default]
+    Text,
+    /// A SARIF 2.1.0 log, e.g. for GitHub code scanning.
+    Sarif,
+    /// A JSON array of findings.
+    Json,
+    /// One JSON object per finding and line.
+    JsonLines,
+    /// GitHub Actions workflow commands that create annotations.
+    Github,
+    /// Human-readable output with the affected lines of the file.
+    Pretty,
+}
+
+impl OutputFormat {
+    /// All output formats.
+    pub const ALL: [OutputFormat; 6] = [
+        OutputFormat::Text,
+        OutputFormat::Sarif,
+        OutputFormat::Json,
+        OutputFormat::JsonLines,
+        OutputFormat::Github,
+        OutputFormat::Pretty,
+    ];
+
+    /// The name of the output format.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            OutputFormat::Text => "text",
+            OutputFormat::Sarif => "sarif",
+            OutputFormat::Json => "json",
+            OutputFormat::JsonLines => "jsonl",
+            OutputFormat::Github => "github",
+            OutputFormat::Pretty => "pretty",
+        }
+    }
+
+    /// Writes the findings in this format.
+    pub fn write<W>(&self, writer: &mut W, findings: &[Finding]) -> io::Result<()>
+    where
+        W: Write,
+    {
+        match self {
+            OutputFormat::Text => text::write(writer, findings),
+            OutputFormat::Sarif => sarif::write(writer, findings),
+            OutputFormat::Json => json::write(writer, findings),
+            OutputFormat::JsonLines => json::write_lines(writer, findings),
+            OutputFormat::Github => github::write(writer, findings),
+            OutputFormat::Pretty => pretty::write(writer, findings),
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        OutputFormat::ALL
+            .into_iter()
+            .find(|format| format.as_str() == s)
+            .ok_or_else(|| format!("unknown output format {:?}", s))
+    }
+}
+
+/// Checks the workflow source (as `build.yml` in a repository at the current directory) and
+/// returns the findings of the policy.
+#[cfg(test)]
+fn check_workflow_findings(source: &str, policy_name: &str) -> Vec<Finding> {
+    check_workflow_findings_at(source, Path::new("build.yml"), Path::new("."), policy_name)
+}
+
+/// Checks the workflow source (as the file at `path` in the repository at `root`) and returns the
+/// findings of the policy.
+#[cfg(test)]
+fn check_workflow_findings_at(
+    source: &str,
+    path: &Path,
+    root: &Path,
+    policy_name: &str,
+) -> Vec<Finding> {
+    let repository = Repository::new(root);
+    let outputs = crate::check_source(
+        source,
+        crate::FileKind::Workflow,
+        path,
+        &crate::Config::default(),
+        &repository,
+    )
+    .unwrap();
+    Finding::from_outputs(path, &repository, &outputs)
+        .into_iter()
+        .filter(|finding| finding.policy().name == policy_name)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_normalize_path() {
+        assert_eq!(
+            normalize_path(Path::new("./.github/workflows/build.yml")),
+            ".github/workflows/build.yml"
+        );
+        assert_eq!(
+            normalize_path(Path::new("/repo/./.github/workflows/build.yml")),
+            "/repo/.github/workflows/build.yml"
+        );
+    }
+
+    #[test]
+    fn test_fingerprint_of_violations_in_same_step() {
+        let source = "on: [issue_comment]\njobs:\n  build:\n    runs-on: ubuntu-latest\n    steps:\n      - run: echo \"${{ github.event.comment.body }} ${{ github.event.issue.title }}\"\n";
+        let fingerprints: Vec<_> = check_workflow_findings(source, "no_untrusted_expr_in_run")
+            .iter()
+            .map(Finding::fingerprint)
+            .collect();
+        assert_eq!(fingerprints.len(), 2);
+        assert_ne!(fingerprints[0], fingerprints[1]);
+    }
+}
@@ -0,0 +1,228 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Human-readable output with source snippets, e.g.:
+//!
+//! ```text
+//! high[no_untrusted_expr_in_run]: Step 1 of job build uses attacker-controlled context ...
+//!  --> .github/workflows/build.yml:7:23
+//!   |
+//! 7 |       - run: echo "${{ github.event.pull_request.title }}"
+//!   |                       ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
+//!   |
+//!   = help: No step should use attacker-controllable contexts in the `run` field.
+//!   = note: for more information, run `ghastly show no_untrusted_expr_in_run`
+//! ```
+
+use super::Finding;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Spans with more lines are shortened to their first and last lines.
+const MAX_SNIPPET_LINES: usize = 4;
+
+/// Writes each finding with the affected lines of the file.
+pub fn write<W>(writer: &mut W, findings: &[Finding]) -> io::Result<()>
+where
+    W: Write,
+{
+    let mut sources: HashMap<&Path, Option<String>> = HashMap::new();
+    findings
+        .iter()
+        .enumerate()
+        .try_for_each(|(index, finding)| {
+            if index > 0 {
+                writeln!(writer)?;
+            }
+            let source = sources
+                .entry(finding.path())
+                .or_insert_with(|| fs::read_to_string(finding.path()).ok());
+            write_finding(writer, finding, source.as_deref())
+        })
+}
+
+/// Writes a single finding. The snippet is omitted if the source is not available.
+fn write_finding<W>(writer: &mut W, finding: &Finding, source: Option<&str>) -> io::Result<()>
+where
+    W: Write,
+{
+    let (start_line, start_column) = finding.start();
+    let (mut end_line, mut end_column) = finding.end();
+    writeln!(
+        writer,
+        "{severity}[{policy_name}]: {message}",
+        severity = finding.severity(),
+        policy_name = finding.policy().name,
+        message = finding.violation().message(),
+    )?;
+
+    let lines: Vec<_> = source
+        .map(|source| source.lines().collect())
+        .unwrap_or_default();
+    // Spans of block mappings and sequences end at the start of the next line (or the next key).
+    let indentation = |line: &str| line.chars().take_while(|c| c.is_whitespace()).count();
+    if end_line > start_line
+        && lines
+            .get(end_line - 1)
+            .is_none_or(|line| end_column <= indentation(line) + 1)
+    {
+        end_line -= 1;
+        end_column = lines
+            .get(end_line.wrapping_sub(1))
+            .map_or(1, |line| line.chars().count() + 1);
+    }
+    let end_line = end_line.max(start_line);
+    let snippet_lines: Vec<usize> = if end_line - start_line < MAX_SNIPPET_LINES {
+        (start_line..=end_line).collect()
+    } else {
+        vec![start_line, start_line + 1, end_line - 1, end_line]
+    };
+    let gutter_width = end_line.to_string().len();
+    let gutter = " ".repeat(gutter_width);
+
+    writeln!(
+        writer,
+        "{gutter}--> {path}:{start_line}:{start_column}",
+        path = finding.path().display()
+    )?;
+    if start_line > 0
+        && snippet_lines
+            .iter()
+            .all(|line| lines.get(line - 1).is_some())
+    {
+        writeln!(writer, "{gutter} |")?;
+        let mut previous_line = None;
+        for line_number in snippet_lines {
+            if previous_line.is_some_and(|previous| previous + 1 < line_number) {
+                writeln!(writer, "...")?;
+            }
+            previous_line = Some(line_number);
+
+            let line = lines[line_number - 1].trim_end();
+            writeln!(writer, "{line_number:>gutter_width$} | {line}")?;
+
+            let length = line.chars().count();
+            let first_column = if line_number == start_line {
+                start_column
+            } else {
+                length - line.trim_start().chars().count() + 1
+            };
+            let last_column = if line_number == end_line {
+                end_column.saturating_sub(1).min(length)
+            } else {
+                length
+            };
+            let underline_length = (last_column + 1).saturating_sub(first_column).max(1);
+            writeln!(
+                writer,
+                "{gutter} | {}{}",
+                " ".repeat(first_column.saturating_sub(1)),
+                "^".repeat(underline_length)
+            )?;
+        }
+        writeln!(writer, "{gutter} |")?;
+    }
+
+    if let Some(summary) = finding.policy().summary() {
+        writeln!(writer, "{gutter} = help: {summary}")?;
+    }
+    if let Some(fix) = finding.violation().fix() {
+        writeln!(
+            writer,
+            "{gutter} = fix: {} (run `ghastly fix`)",
+            fix.description()
+        )?;
+    }
+    writeln!(
+        writer,
+        "{gutter} = note: for more information, run `ghastly show {}`",
+        finding.policy().name
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{get_policies, PolicyCheckOutput, PolicyViolation, Repository};
+    use marked_yaml::{Marker, Span};
+
+    fn render(source: &str, start: (usize, usize), end: (usize, usize)) -> String {
+        let policy = get_policies()
+            .find(|policy| policy.name == "permissions_set")
+            .unwrap();
+        let violation = PolicyViolation::new(
+            Span::new_with_marks(
+                Marker::new(0, start.0, start.1),
+                Marker::new(0, end.0, end.1),
+            ),
+            "Message.".to_owned(),
+        );
+        let findings = Finding::from_outputs(
+            Path::new("build.yml"),
+            &Repository::default(),
+            &[PolicyCheckOutput::new(policy, vec![violation])],
+        );
+        let mut output = Vec::new();
+        write_finding(&mut output, &findings[0], Some(source)).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_write_finding() {
+        let source = "on: push\njobs:\n  build:\n    runs-on: ubuntu-latest\n    steps:\n      - run: echo\n      - run: echo\n";
+        let help = get_policies()
+            .find(|policy| policy.name == "permissions_set")
+            .and_then(|policy| policy.summary())
+            .unwrap();
+        assert_eq!(
+            render(source, (4, 14), (4, 27)),
+            format!(
+                "medium[permissions_set]: Message.
+ --> build.yml:4:14
+  |
+4 |     runs-on: ubuntu-latest
+  |              ^^^^^^^^^^^^^
+  |
+  = help: {help}
+  = note: for more information, run `ghastly show permissions_set`
+"
+            )
+        );
+        assert!(render(source, (3, 3), (8, 1)).contains(
+            "
+  |
+3 |   build:
+  |   ^^^^^^
+4 |     runs-on: ubuntu-latest
+  |     ^^^^^^^^^^^^^^^^^^^^^^
+...
+6 |       - run: echo
+  |       ^^^^^^^^^^^
+7 |       - run: echo
+  |       ^^^^^^^^^^^
+  |
+"
+        ));
+    }
+
+    #[test]
+    fn test_write_scalar_finding() {
+        let source = "on: push\njobs:\n  build:\n    runs-on: ubuntu-latest\n    permissions: write-all\n    steps:\n      - uses: actions/checkout@v4\n";
+        let findings = super::super::check_workflow_findings(source, "actions_pinned");
+        let mut output = Vec::new();
+        write_finding(&mut output, &findings[0], Some(source)).unwrap();
+        assert!(String::from_utf8(output).unwrap().contains(
+            "
+7 |       - uses: actions/checkout@v4
+  |               ^^^^^^^^^^^^^^^^^^^
+"
+        ));
+    }
+}
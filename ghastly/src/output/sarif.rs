@@ -0,0 +1,256 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! SARIF 2.1.0 output.
+//!
+//! Specification: <https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html>
+//!
+//! GitHub code scanning only supports a subset of SARIF, see
+//! <https://docs.github.com/en/code-security/code-scanning/integrating-with-code-scanning/sarif-support-for-code-scanning>.
+
+use super::{normalize_path, Finding};
+use crate::policies::{get_policies, Confidence, Policy, Severity};
+use serde_json::{json, Value};
+use std::io::{self, Write};
+use std::path::Path;
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const INFORMATION_URI: &str = "https://github.com/Holzhaus/ghastly";
+
+/// The SARIF level of a severity.
+const fn level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::High => "error",
+        Severity::Medium => "warning",
+        Severity::Low | Severity::Info => "note",
+    }
+}
+
+/// The score used by GitHub code scanning to classify security findings (if any).
+const fn security_severity(severity: Severity) -> Option<&'static str> {
+    match severity {
+        Severity::High => Some("8.0"),
+        Severity::Medium => Some("5.0"),
+        Severity::Low => Some("2.0"),
+        Severity::Info => None,
+    }
+}
+
+/// The SARIF precision of a confidence level.
+const fn precision(confidence: Confidence) -> &'static str {
+    match confidence {
+        Confidence::Low => "low",
+        Confidence::Medium => "medium",
+        Confidence::High => "high",
+    }
+}
+
+/// Converts the path of a file relative to the repository root into a URI reference.
+///
+/// The URI is resolved against the `%SRCROOT%` base, which is the repository root when uploading
+/// the results to GitHub.
+fn artifact_location(repository_path: &Path) -> Value {
+    json!({ "uri": normalize_path(repository_path), "uriBaseId": "%SRCROOT%" })
+}
+
+fn rule(policy: &Policy) -> Value {
+    let severity = policy.severity;
+    let tags: Vec<_> = ["security"]
+        .into_iter()
+        .chain(policy.category)
+        .chain(policy.tags.iter().copied())
+        .collect();
+    let mut properties = json!({
+        "tags": tags,
+        "precision": precision(policy.confidence),
+    });
+    if let Some(security_severity) = security_severity(severity) {
+        properties["security-severity"] = json!(security_severity);
+    }
+
+    let mut rule = json!({
+        "id": policy.name,
+        "name": policy.name,
+        "defaultConfiguration": { "level": level(severity) },
+        "properties": properties,
+    });
+    if let Some(summary) = policy.summary() {
+        rule["shortDescription"] = json!({ "text": summary });
+    }
+    if let Some(doc) = policy.doc {
+        rule["help"] = json!({ "text": doc, "markdown": doc });
+    }
+    rule
+}
+
+fn result(finding: &Finding, rule_index: Option<usize>) -> Value {
+    let (start_line, start_column) = finding.start();
+    let (end_line, end_column) = finding.end();
+    let mut region = json!({ "startLine": start_line.max(1) });
+    if start_column > 0 {
+        region["startColumn"] = json!(start_column);
+    }
+    if (end_line, end_column) > (start_line, start_column) {
+        region["endLine"] = json!(end_line);
+        region["endColumn"] = json!(end_column);
+    }
+
+    let mut result = json!({
+        "ruleId": finding.policy().name,
+        "level": level(finding.severity()),
+        "message": { "text": finding.violation().message() },
+        "partialFingerprints": { "ghastly/v1": finding.fingerprint() },
+        "locations": [{
+            "physicalLocation": {
+                "artifactLocation": artifact_location(finding.repository_path()),
+                "region": region,
+            },
+        }],
+    });
+    if let Some(rule_index) = rule_index {
+        result["ruleIndex"] = json!(rule_index);
+    }
+    result
+}
+
+/// Writes a SARIF log containing all registered policies as rules and the findings as results.
+pub fn write<W>(writer: &mut W, findings: &[Finding]) -> io::Result<()>
+where
+    W: Write,
+{
+    let mut policies: Vec<_> = get_policies().collect();
+    policies.sort_by_key(|policy| policy.name);
+
+    let results: Vec<_> = findings
+        .iter()
+        .map(|finding| {
+            let rule_index = policies
+                .iter()
+                .position(|policy| policy.name == finding.policy().name);
+            result(finding, rule_index)
+        })
+        .collect();
+    let log = json!({
+        "$schema": SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": INFORMATION_URI,
+                    "rules": policies.into_iter().map(rule).collect::<Vec<_>>(),
+                },
+            },
+            "columnKind": "unicodeCodePoints",
+            "results": results,
+        }],
+    });
+    serde_json::to_writer_pretty(&mut *writer, &log)?;
+    writeln!(writer)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_artifact_location() {
+        assert_eq!(
+            artifact_location(Path::new("./.github/workflows/build.yml")),
+            json!({ "uri": ".github/workflows/build.yml", "uriBaseId": "%SRCROOT%" })
+        );
+    }
+
+    #[test]
+    fn test_write() {
+        let source = "on: pull_request\njobs:\n  build:\n    runs-on: ubuntu-latest\n    permissions: write-all\n    steps:\n      - run: echo \"${{ github.head_ref }}\"\n";
+        let findings: Vec<_> = ["no_all_permissions", "no_untrusted_expr_in_run"]
+            .into_iter()
+            .flat_map(|policy_name| super::super::check_workflow_findings(source, policy_name))
+            .collect();
+        let mut output = Vec::new();
+        write(&mut output, &findings).unwrap();
+        let log: Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(log["version"], "2.1.0");
+        let run = &log["runs"][0];
+
+        // All policies are included as rules, sorted by name.
+        let rules = run["tool"]["driver"]["rules"].as_array().unwrap();
+        assert_eq!(rules.len(), get_policies().count());
+        let rule = |name: &str| {
+            rules
+                .iter()
+                .position(|rule| rule["id"] == name)
+                .map(|index| (index, &rules[index]))
+                .unwrap()
+        };
+        let (all_permissions_index, all_permissions) = rule("no_all_permissions");
+        assert_eq!(all_permissions["defaultConfiguration"]["level"], "warning");
+        assert_eq!(all_permissions["properties"]["security-severity"], "5.0");
+        assert_eq!(all_permissions["properties"]["precision"], "high");
+        assert_eq!(
+            all_permissions["properties"]["tags"],
+            json!(["security", "permissions", "github-token"])
+        );
+        let (untrusted_expr_index, untrusted_expr) = rule("no_untrusted_expr_in_run");
+        assert_eq!(untrusted_expr["defaultConfiguration"]["level"], "error");
+        assert_eq!(untrusted_expr["properties"]["security-severity"], "8.0");
+        let (_, unused_suppression) = rule("unused_suppression");
+        assert_eq!(unused_suppression["defaultConfiguration"]["level"], "note");
+        assert!(unused_suppression["properties"]
+            .get("security-severity")
+            .is_none());
+
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(
+            results[0],
+            json!({
+                "ruleId": "no_all_permissions",
+                "ruleIndex": all_permissions_index,
+                "level": "warning",
+                "message": { "text": "Job build should not use the 'write-all' permission." },
+                "partialFingerprints": { "ghastly/v1": findings[0].fingerprint() },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": "build.yml", "uriBaseId": "%SRCROOT%" },
+                        "region": {
+                            "startLine": 5,
+                            "startColumn": 18,
+                            "endLine": 5,
+                            "endColumn": 27,
+                        },
+                    },
+                }],
+            })
+        );
+        assert_eq!(results[1]["ruleIndex"], untrusted_expr_index);
+        assert_eq!(results[1]["level"], "error");
+        assert_eq!(
+            results[1]["partialFingerprints"]["ghastly/v1"],
+            findings[1].fingerprint()
+        );
+
+        // A file checked from a subdirectory of the repository is still relative to its root.
+        let findings = super::super::check_workflow_findings_at(
+            source,
+            Path::new("workflows/build.yml"),
+            Path::new(".."),
+            "no_all_permissions",
+        );
+        let mut output = Vec::new();
+        write(&mut output, &findings).unwrap();
+        let log: Value = serde_json::from_slice(&output).unwrap();
+        let current_dir = std::env::current_dir().unwrap();
+        let subdirectory = current_dir.file_name().unwrap().to_str().unwrap();
+        assert_eq!(
+            log["runs"][0]["results"][0]["locations"][0]["physicalLocation"]["artifactLocation"],
+            json!({ "uri": format!("{subdirectory}/workflows/build.yml"), "uriBaseId": "%SRCROOT%" })
+        );
+    }
+}
@@ -0,0 +1,28 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+use super::Finding;
+use std::io::{self, Write};
+
+/// Writes one line per finding in the form `path:line:column:message (policy, severity)`.
+pub fn write<W>(writer: &mut W, findings: &[Finding]) -> io::Result<()>
+where
+    W: Write,
+{
+    findings.iter().try_for_each(|finding| {
+        let (line, column) = finding.start();
+        writeln!(
+            writer,
+            "{path}:{line}:{column}:{message} ({policy_name}, {severity})",
+            path = finding.path().display(),
+            message = finding.violation().message(),
+            policy_name = finding.policy().name,
+            severity = finding.severity(),
+        )
+    })
+}
@@ -0,0 +1,309 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+use super::source::Source;
+use super::workflow::{Map, Step, StringMap};
+use marked_yaml::Spanned;
+use serde::Deserialize;
+use std::convert::TryFrom;
+use std::io::Read;
+
+/// The metadata of an action (i.e., the contents of an `action.yml` file).
+///
+/// Documentation: <https://docs.github.com/en/actions/sharing-automations/creating-actions/metadata-syntax-for-github-actions>
+#[derive(Deserialize, Debug)]
+pub struct Action {
+    /// The name of the action.
+    pub name: Option<Spanned<String>>,
+    /// The name of the action's author.
+    pub author: Option<Spanned<String>>,
+    /// A short description of the action.
+    pub description: Option<Spanned<String>>,
+    /// Input parameters that are available to the action at runtime.
+    pub inputs: Option<Spanned<Map<ActionInput>>>,
+    /// Output parameters that the action sets.
+    pub outputs: Option<Spanned<Map<ActionOutput>>>,
+    /// Specifies whether this is a JavaScript, composite or Docker action and how it is executed.
+    pub runs: Spanned<Runs>,
+    /// The YAML source of this action.
+    #[serde(skip)]
+    source: Source,
+}
+
+impl Action {
+    /// Parse action metadata from the given reader.
+    pub fn from_reader<R>(reader: &mut R) -> crate::Result<Action>
+    where
+        R: Read,
+    {
+        let source = Source::from_reader(reader)?;
+        let mut action: Action = source.deserialize()?;
+        action.source = source;
+        Ok(action)
+    }
+
+    /// The YAML source of this action.
+    #[inline]
+    pub fn source(&self) -> &Source {
+        &self.source
+    }
+
+    /// The steps of a composite action (other actions do not have any).
+    pub fn steps(&self) -> Option<&Spanned<Vec<Step>>> {
+        match &*self.runs {
+            Runs::Composite(runs) => Some(&runs.steps),
+            Runs::JavaScript(_) | Runs::Docker(_) => None,
+        }
+    }
+}
+
+/// An input parameter of an action.
+///
+/// Documentation: <https://docs.github.com/en/actions/sharing-automations/creating-actions/metadata-syntax-for-github-actions#inputs>
+#[derive(Deserialize, Debug)]
+pub struct ActionInput {
+    /// A description of the input parameter.
+    pub description: Option<Spanned<String>>,
+    /// Whether the input parameter is required.
+    pub required: Option<Spanned<String>>,
+    /// The default value, which is used if the input parameter is not specified.
+    pub default: Option<Spanned<String>>,
+    /// A warning message that is shown if the input parameter is used.
+    #[serde(rename = "deprecationMessage")]
+    pub deprecation_message: Option<Spanned<String>>,
+}
+
+/// An output parameter of an action.
+///
+/// Documentation: <https://docs.github.com/en/actions/sharing-automations/creating-actions/metadata-syntax-for-github-actions#outputs-for-docker-container-and-javascript-actions>
+#[derive(Deserialize, Debug)]
+pub struct ActionOutput {
+    /// A description of the output parameter.
+    pub description: Option<Spanned<String>>,
+    /// The value of the output parameter (composite actions only).
+    pub value: Option<Spanned<String>>,
+}
+
+/// Specifies how an action is executed.
+///
+/// Documentation: <https://docs.github.com/en/actions/sharing-automations/creating-actions/metadata-syntax-for-github-actions#runs>
+#[derive(Deserialize, Debug)]
+#[serde(try_from = "RunsSyntax")]
+pub enum Runs {
+    /// A composite action, which runs a sequence of steps.
+    Composite(CompositeRuns),
+    /// A JavaScript action, which runs a script with Node.js (e.g. `using: node20`).
+    JavaScript(JavaScriptRuns),
+    /// A Docker container action.
+    Docker(DockerRuns),
+}
+
+/// The configuration of a composite action.
+#[derive(Debug)]
+pub struct CompositeRuns {
+    /// The steps that the action runs.
+    pub steps: Spanned<Vec<Step>>,
+}
+
+/// The configuration of a JavaScript action.
+#[derive(Debug)]
+pub struct JavaScriptRuns {
+    /// The Node.js runtime, e.g. `node20`.
+    pub using: Spanned<String>,
+    /// The file that contains the action code.
+    pub main: Spanned<String>,
+    /// A script that runs at the start of a job, before `main`.
+    pub pre: Option<Spanned<String>>,
+    /// A conditional for the `pre` script.
+    pub pre_if: Option<Spanned<String>>,
+    /// A script that runs at the end of a job, after `main`.
+    pub post: Option<Spanned<String>>,
+    /// A conditional for the `post` script.
+    pub post_if: Option<Spanned<String>>,
+}
+
+/// The configuration of a Docker container action.
+#[derive(Debug)]
+pub struct DockerRuns {
+    /// The Docker image, either a `Dockerfile` in the repository or a public registry image
+    /// (e.g. `docker://debian:stretch-slim`).
+    pub image: Spanned<String>,
+    /// Environment variables to set in the container.
+    pub env: Option<Spanned<StringMap>>,
+    /// The arguments that are passed to the entrypoint of the container.
+    pub args: Option<Spanned<Vec<Spanned<String>>>>,
+    /// Overrides the `ENTRYPOINT` of the image.
+    pub entrypoint: Option<Spanned<String>>,
+    /// An entrypoint that runs before the `entrypoint`.
+    pub pre_entrypoint: Option<Spanned<String>>,
+    /// A conditional for the `pre-entrypoint`.
+    pub pre_if: Option<Spanned<String>>,
+    /// An entrypoint that runs after the `entrypoint`.
+    pub post_entrypoint: Option<Spanned<String>>,
+    /// A conditional for the `post-entrypoint`.
+    pub post_if: Option<Spanned<String>>,
+}
+
+impl DockerRuns {
+    /// The arguments and entrypoints of the container, together with the names of their fields.
+    ///
+    /// Expressions in these fields are substituted before the container is started.
+    pub fn commands(&self) -> Vec<(&'static str, &Spanned<String>)> {
+        let args = self
+            .args
+            .iter()
+            .flat_map(|args| args.iter())
+            .map(|arg| ("args", arg));
+        let entrypoints = [
+            ("pre-entrypoint", &self.pre_entrypoint),
+            ("entrypoint", &self.entrypoint),
+            ("post-entrypoint", &self.post_entrypoint),
+        ]
+        .into_iter()
+        .filter_map(|(field, entrypoint)| Some((field, entrypoint.as_ref()?)));
+        args.chain(entrypoints).collect()
+    }
+}
+
+/// The union of the fields of all kinds of actions, which is used to deserialize [`Runs`]
+/// without losing the spans of nested values.
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct RunsSyntax {
+    using: Spanned<String>,
+    steps: Option<Spanned<Vec<Step>>>,
+    main: Option<Spanned<String>>,
+    pre: Option<Spanned<String>>,
+    pre_if: Option<Spanned<String>>,
+    post: Option<Spanned<String>>,
+    post_if: Option<Spanned<String>>,
+    image: Option<Spanned<String>>,
+    env: Option<Spanned<StringMap>>,
+    args: Option<Spanned<Vec<Spanned<String>>>>,
+    entrypoint: Option<Spanned<String>>,
+    pre_entrypoint: Option<Spanned<String>>,
+    post_entrypoint: Option<Spanned<String>>,
+}
+
+impl TryFrom<RunsSyntax> for Runs {
+    type Error = String;
+
+    fn try_from(runs: RunsSyntax) -> Result<Self, Self::Error> {
+        let using = runs.using.as_str().to_owned();
+        match using.as_str() {
+            "composite" => Ok(Runs::Composite(CompositeRuns {
+                steps: runs.steps.ok_or("missing field `steps`")?,
+            })),
+            "docker" => Ok(Runs::Docker(DockerRuns {
+                image: runs.image.ok_or("missing field `image`")?,
+                env: runs.env,
+                args: runs.args,
+                entrypoint: runs.entrypoint,
+                pre_entrypoint: runs.pre_entrypoint,
+                pre_if: runs.pre_if,
+                post_entrypoint: runs.post_entrypoint,
+                post_if: runs.post_if,
+            })),
+            using if using.starts_with("node") => Ok(Runs::JavaScript(JavaScriptRuns {
+                main: runs.main.ok_or("missing field `main`")?,
+                using: runs.using,
+                pre: runs.pre,
+                pre_if: runs.pre_if,
+                post: runs.post,
+                post_if: runs.post_if,
+            })),
+            using => Err(format!(
+                "unknown action type {using:?} (expected 'composite', 'docker' or a Node.js version)"
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_composite_action() {
+        let source = r#"
+name: Greet
+description: Greets someone
+inputs:
+  who-to-greet:
+    description: Who to greet
+    required: true
+    default: World
+outputs:
+  greeting:
+    description: The greeting
+    value: ${{ steps.greet.outputs.greeting }}
+runs:
+  using: composite
+  steps:
+    - id: greet
+      run: echo "greeting=Hello ${{ inputs.who-to-greet }}" >> "$GITHUB_OUTPUT"
+      shell: bash
+    - uses: actions/checkout@v4
+"#;
+        let action = Action::from_reader(&mut source.as_bytes()).unwrap();
+        assert_eq!(action.name.as_ref().unwrap().as_str(), "Greet");
+        let inputs = action.inputs.as_ref().unwrap();
+        assert_eq!(
+            inputs["who-to-greet"].default.as_ref().unwrap().as_str(),
+            "World"
+        );
+        assert!(action.outputs.as_ref().unwrap()["greeting"].value.is_some());
+        let steps = action.steps().unwrap();
+        assert_eq!(steps.len(), 2);
+        assert_eq!(
+            steps[0]
+                .run
+                .as_ref()
+                .unwrap()
+                .span()
+                .start()
+                .unwrap()
+                .line(),
+            17
+        );
+    }
+
+    #[test]
+    fn test_parse_javascript_and_docker_actions() {
+        let source = "runs:\n  using: node20\n  main: dist/index.js\n  post: dist/cleanup.js\n";
+        let action = Action::from_reader(&mut source.as_bytes()).unwrap();
+        let Runs::JavaScript(runs) = &*action.runs else {
+            panic!("expected a JavaScript action");
+        };
+        assert_eq!(runs.main.as_str(), "dist/index.js");
+        assert!(runs.pre.is_none() && runs.post.is_some());
+        assert!(action.steps().is_none());
+
+        let source = "runs:\n  using: docker\n  image: Dockerfile\n  args:\n    - ${{ inputs.foo }}\n  entrypoint: /entrypoint.sh\n";
+        let action = Action::from_reader(&mut source.as_bytes()).unwrap();
+        let Runs::Docker(runs) = &*action.runs else {
+            panic!("expected a Docker action");
+        };
+        assert_eq!(runs.image.as_str(), "Dockerfile");
+        let commands: Vec<_> = runs
+            .commands()
+            .into_iter()
+            .map(|(field, value)| (field, value.as_str()))
+            .collect();
+        assert_eq!(
+            commands,
+            [
+                ("args", "${{ inputs.foo }}"),
+                ("entrypoint", "/entrypoint.sh")
+            ]
+        );
+
+        let source = "runs:\n  using: composite\n";
+        assert!(Action::from_reader(&mut source.as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,279 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+use serde::Deserialize;
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+/// A reference to an action (or reusable workflow) in a `uses` field.
+///
+/// Documentation: <https://docs.github.com/en/actions/writing-workflows/workflow-syntax-for-github-actions#jobsjob_idstepsuses>
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(try_from = "String")]
+pub enum ActionRef {
+    /// An action in the same repository as the workflow, e.g. `./.github/actions/foo`.
+    Local {
+        /// The path relative to the repository root, including the leading `./`.
+        path: String,
+    },
+    /// A Docker image, e.g. `docker://alpine:3.8`.
+    Docker {
+        /// The image name, including the registry (if any).
+        image: String,
+        /// The tag of the image (if any).
+        tag: Option<String>,
+        /// The digest of the image (if any), e.g. `sha256:...`.
+        digest: Option<String>,
+    },
+    /// An action in a public repository, e.g. `actions/checkout@v4`.
+    Repository {
+        /// The owner of the repository.
+        owner: String,
+        /// The name of the repository.
+        repo: String,
+        /// The path to the action inside the repository (if any).
+        path: Option<String>,
+        /// The Git reference (branch, tag or commit SHA).
+        git_ref: String,
+    },
+}
+
+impl ActionRef {
+    /// Returns `true` if the reference is immutable, i.e. pinned to a full commit SHA (for
+    /// repository actions) or a digest (for Docker images).
+    ///
+    /// Local actions are versioned together with the workflow and always considered pinned.
+    pub fn is_pinned(&self) -> bool {
+        match self {
+            ActionRef::Local { .. } => true,
+            ActionRef::Docker { digest, .. } => digest.as_deref().is_some_and(is_image_digest),
+            ActionRef::Repository { git_ref, .. } => is_commit_sha(git_ref),
+        }
+    }
+
+    /// Returns `true` if this is a reference to the repository action `owner/repo` (ignoring
+    /// case, path and Git reference).
+    pub fn is_repository(&self, name: &str) -> bool {
+        match self {
+            ActionRef::Repository { owner, repo, .. } => {
+                name.split_once('/')
+                    .is_some_and(|(expected_owner, expected_repo)| {
+                        owner.eq_ignore_ascii_case(expected_owner)
+                            && repo.eq_ignore_ascii_case(expected_repo)
+                    })
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the action matches the given pattern.
+    ///
+    /// Patterns have the form `owner/*` (all actions of an owner), `owner/repo` (all actions in
+    /// a repository) or `owner/repo/path` (a single action in a repository). Matching is case
+    /// insensitive. Local and Docker actions never match.
+    pub fn matches(&self, pattern: &str) -> bool {
+        let ActionRef::Repository {
+            owner, repo, path, ..
+        } = self
+        else {
+            return false;
+        };
+        let mut parts = pattern.splitn(3, '/');
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(expected_owner), Some("*"), None) => owner.eq_ignore_ascii_case(expected_owner),
+            (Some(expected_owner), Some(expected_repo), expected_path) => {
+                owner.eq_ignore_ascii_case(expected_owner)
+                    && repo.eq_ignore_ascii_case(expected_repo)
+                    && (expected_path.is_none()
+                        || expected_path.zip(path.as_deref()).is_some_and(
+                            |(expected_path, path)| expected_path.eq_ignore_ascii_case(path),
+                        ))
+            }
+            _ => false,
+        }
+    }
+
+    /// The name of the action without the Git reference, e.g. `actions/checkout` or
+    /// `github/codeql-action/init`.
+    pub fn name(&self) -> String {
+        match self {
+            ActionRef::Local { path } => path.clone(),
+            ActionRef::Docker { image, .. } => format!("docker://{image}"),
+            ActionRef::Repository {
+                owner,
+                repo,
+                path: None,
+                ..
+            } => format!("{owner}/{repo}"),
+            ActionRef::Repository {
+                owner,
+                repo,
+                path: Some(path),
+                ..
+            } => format!("{owner}/{repo}/{path}"),
+        }
+    }
+}
+
+/// Returns `true` if the string is a full (40 characters) hexadecimal commit SHA.
+fn is_commit_sha(value: &str) -> bool {
+    value.len() == 40 && value.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Returns `true` if the string is a full SHA-256 image digest (`sha256:` followed by 64
+/// hexadecimal characters).
+fn is_image_digest(value: &str) -> bool {
+    value
+        .strip_prefix("sha256:")
+        .is_some_and(|hash| hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+impl TryFrom<String> for ActionRef {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        ActionRef::from_str(&value)
+    }
+}
+
+impl FromStr for ActionRef {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.starts_with("./") {
+            return Ok(ActionRef::Local { path: s.to_owned() });
+        }
+
+        if let Some(image) = s.strip_prefix("docker://") {
+            let (image, digest) = match image.split_once('@') {
+                Some((image, digest)) => (image, Some(digest.to_owned())),
+                None => (image, None),
+            };
+            // The tag separator must not be confused with the port of a registry host.
+            let (image, tag) = match image.rsplit_once(':') {
+                Some((name, tag)) if !tag.contains('/') => (name, Some(tag.to_owned())),
+                _ => (image, None),
+            };
+            if image.is_empty() {
+                return Err(format!("invalid docker action reference {:?}", s));
+            }
+            return Ok(ActionRef::Docker {
+                image: image.to_owned(),
+                tag,
+                digest,
+            });
+        }
+
+        let (name, git_ref) = s
+            .split_once('@')
+            .ok_or_else(|| format!("action reference {:?} is missing a version", s))?;
+        let mut parts = name.splitn(3, '/');
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(owner), Some(repo), path)
+                if !owner.is_empty() && !repo.is_empty() && !git_ref.is_empty() =>
+            {
+                Ok(ActionRef::Repository {
+                    owner: owner.to_owned(),
+                    repo: repo.to_owned(),
+                    path: path.map(str::to_owned),
+                    git_ref: git_ref.to_owned(),
+                })
+            }
+            _ => Err(format!("invalid action reference {:?}", s)),
+        }
+    }
+}
+
+impl fmt::Display for ActionRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ActionRef::Local { path } => f.write_str(path),
+            ActionRef::Docker { image, tag, digest } => {
+                write!(f, "docker://{image}")?;
+                if let Some(tag) = tag {
+                    write!(f, ":{tag}")?;
+                }
+                if let Some(digest) = digest {
+                    write!(f, "@{digest}")?;
+                }
+                Ok(())
+            }
+            ActionRef::Repository { git_ref, .. } => write!(f, "{}@{git_ref}", self.name()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_action_ref() {
+        assert_eq!(
+            "actions/checkout@v4".parse(),
+            Ok(ActionRef::Repository {
+                owner: "actions".into(),
+                repo: "checkout".into(),
+                path: None,
+                git_ref: "v4".into(),
+            })
+        );
+        assert_eq!(
+            "github/codeql-action/init@1b549b9259bda1cb5ddde3b41741a82a2d15a841".parse(),
+            Ok(ActionRef::Repository {
+                owner: "github".into(),
+                repo: "codeql-action".into(),
+                path: Some("init".into()),
+                git_ref: "1b549b9259bda1cb5ddde3b41741a82a2d15a841".into(),
+            })
+        );
+        assert_eq!(
+            "./.github/actions/foo".parse(),
+            Ok(ActionRef::Local {
+                path: "./.github/actions/foo".into()
+            })
+        );
+        assert_eq!(
+            "docker://ghcr.io:443/foo/bar:1.0@sha256:3e5e1a2b6d4c7f8091a2b3c4d5e6f7083e5e1a2b6d4c7f8091a2b3c4d5e6f708".parse(),
+            Ok(ActionRef::Docker {
+                image: "ghcr.io:443/foo/bar".into(),
+                tag: Some("1.0".into()),
+                digest: Some("sha256:3e5e1a2b6d4c7f8091a2b3c4d5e6f7083e5e1a2b6d4c7f8091a2b3c4d5e6f708".into()),
+            })
+        );
+        assert!("actions/checkout".parse::<ActionRef>().is_err());
+        assert!("checkout@v4".parse::<ActionRef>().is_err());
+    }
+
+    #[test]
+    fn test_matches() {
+        let action: ActionRef = "github/codeql-action/init@v3".parse().unwrap();
+        assert!(action.matches("github/*"));
+        assert!(action.matches("GitHub/codeql-action"));
+        assert!(action.matches("github/codeql-action/init"));
+        assert!(!action.matches("github/codeql-action/analyze"));
+        assert!(!action.matches("actions/*"));
+        assert!(!action.matches("github"));
+    }
+
+    #[test]
+    fn test_is_pinned() {
+        let pinned = |s: &str| s.parse::<ActionRef>().unwrap().is_pinned();
+        assert!(!pinned("actions/checkout@v4"));
+        assert!(!pinned("actions/checkout@main"));
+        assert!(pinned(
+            "actions/checkout@11bd71901bbe5b1630ceea73d27597364c9af683"
+        ));
+        assert!(!pinned("docker://alpine:3.8"));
+        assert!(pinned("docker://alpine@sha256:3e5e1a2b6d4c7f8091a2b3c4d5e6f7083e5e1a2b6d4c7f8091a2b3c4d5e6f708"));
+        assert!(!pinned("docker://alpine@sha256:abc"));
+        assert!(!pinned("docker://alpine@latest"));
+        assert!(pinned("./local"));
+    }
+}
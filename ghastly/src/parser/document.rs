@@ -0,0 +1,148 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+use super::action::Action;
+use super::source::Source;
+use super::workflow::{Job, Step, Workflow};
+use crate::FileKind;
+use marked_yaml::types::MarkedMappingNode;
+use std::io::Read;
+
+/// A file that is checked by the policies.
+///
+/// Policies either check a [`Workflow`], an [`Action`] or any document (e.g. to check the steps
+/// of workflows and composite actions alike).
+#[derive(Debug)]
+pub enum Document {
+    /// A workflow file, e.g. `.github/workflows/build.yml`.
+    Workflow(Workflow),
+    /// An action metadata file, e.g. `.github/actions/foo/action.yml`.
+    Action(Action),
+}
+
+impl Document {
+    /// Parse a document of the given kind from the given reader.
+    pub fn from_reader<R>(reader: &mut R, kind: FileKind) -> crate::Result<Document>
+    where
+        R: Read,
+    {
+        Ok(match kind {
+            FileKind::Workflow => Document::Workflow(Workflow::from_reader(reader)?),
+            FileKind::Action => Document::Action(Action::from_reader(reader)?),
+        })
+    }
+
+    /// The kind of file that this document was parsed from.
+    pub fn kind(&self) -> FileKind {
+        match self {
+            Document::Workflow(_) => FileKind::Workflow,
+            Document::Action(_) => FileKind::Action,
+        }
+    }
+
+    /// The workflow, if this document is a workflow.
+    pub fn as_workflow(&self) -> Option<&Workflow> {
+        match self {
+            Document::Workflow(workflow) => Some(workflow),
+            Document::Action(_) => None,
+        }
+    }
+
+    /// The action, if this document is an action.
+    pub fn as_action(&self) -> Option<&Action> {
+        match self {
+            Document::Workflow(_) => None,
+            Document::Action(action) => Some(action),
+        }
+    }
+
+    /// The YAML source of this document.
+    pub fn source(&self) -> &Source {
+        match self {
+            Document::Workflow(workflow) => workflow.source(),
+            Document::Action(action) => action.source(),
+        }
+    }
+
+    /// The steps of all jobs of a workflow or the steps of a composite action.
+    pub fn steps(&self) -> Vec<DocumentStep<'_>> {
+        match self {
+            Document::Workflow(workflow) => workflow
+                .jobs
+                .iter()
+                .flat_map(|(job_name, job)| {
+                    job.steps().into_iter().flat_map(move |steps| {
+                        steps
+                            .iter()
+                            .enumerate()
+                            .map(move |(index, step)| DocumentStep {
+                                job: Some((job_name.as_str(), &**job)),
+                                index,
+                                step,
+                            })
+                    })
+                })
+                .collect(),
+            Document::Action(action) => action
+                .steps()
+                .into_iter()
+                .flat_map(|steps| {
+                    steps.iter().enumerate().map(|(index, step)| DocumentStep {
+                        job: None,
+                        index,
+                        step,
+                    })
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A step of a job in a workflow or of a composite action.
+#[derive(Debug, Clone, Copy)]
+pub struct DocumentStep<'a> {
+    /// The ID and the job that the step belongs to (`None` for steps of composite actions).
+    pub job: Option<(&'a str, &'a Job)>,
+    /// The 0-based index of the step.
+    pub index: usize,
+    /// The step itself.
+    pub step: &'a Step,
+}
+
+impl<'a> DocumentStep<'a> {
+    /// The ID of the job that the step belongs to.
+    pub fn job_name(&self) -> Option<&'a str> {
+        self.job.map(|(job_name, _job)| job_name)
+    }
+
+    /// A key that identifies the step (see [`Step::key`]).
+    pub fn key(&self) -> String {
+        self.step.key(self.index)
+    }
+
+    /// A description of the step for messages, e.g. `Step 1 of job build`.
+    pub fn description(&self) -> String {
+        match self.job_name() {
+            Some(job_name) => format!("Step {} of job {}", self.index + 1, job_name),
+            None => format!("Step {} of the action", self.index + 1),
+        }
+    }
+
+    /// The YAML node of the step in the document, including the locations of its keys.
+    pub fn node<'b>(&self, document: &'b Document) -> Option<&'b MarkedMappingNode> {
+        let root = document.source().node().as_mapping()?;
+        let steps = match self.job_name() {
+            Some(job_name) => root
+                .get_mapping("jobs")?
+                .get_mapping(job_name)?
+                .get_sequence("steps")?,
+            None => root.get_mapping("runs")?.get_sequence("steps")?,
+        };
+        steps.get_mapping(self.index)
+    }
+}
@@ -0,0 +1,231 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+use super::location::scalar_span;
+use marked_yaml::{FromNodeError, LoadError, Node, Span};
+use std::fmt;
+
+/// An error while parsing a YAML file, with the location of the offending node.
+#[derive(Debug, Clone)]
+pub struct ParseError {
+    span: Span,
+    path: Option<String>,
+    message: String,
+}
+
+/// A segment of the path to a node, as reported by `serde_path_to_error`.
+#[derive(Debug, PartialEq, Eq)]
+enum PathSegment<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+impl ParseError {
+    /// Creates an error at the given location.
+    pub(super) fn new(span: Span, path: Option<String>, message: String) -> Self {
+        Self {
+            span,
+            path,
+            message,
+        }
+    }
+
+    /// Creates an error for a file that is not valid YAML.
+    pub fn from_load_error(err: &LoadError) -> Self {
+        let marker = match err {
+            LoadError::TopLevelMustBeMapping(marker)
+            | LoadError::TopLevelMustBeSequence(marker)
+            | LoadError::UnexpectedAnchor(marker)
+            | LoadError::MappingKeyMustBeScalar(marker)
+            | LoadError::UnexpectedTag(marker)
+            | LoadError::ScanError(marker, _) => Some(*marker),
+            LoadError::DuplicateKey(inner) => inner.key.span().start().copied(),
+            _ => None,
+        };
+        // The message of a load error starts with its location, which is stored separately.
+        let message = err.to_string();
+        let message = marker
+            .and_then(|marker| message.strip_prefix(&format!("{marker}: ")))
+            .unwrap_or(&message)
+            .to_owned();
+        Self {
+            span: marker.map_or_else(Span::new_blank, Span::new_start),
+            path: None,
+            message,
+        }
+    }
+
+    /// Creates an error for a YAML document that does not have the expected structure.
+    ///
+    /// The location reported by the deserializer usually is the start of the enclosing job, so
+    /// the location is looked up in the document using the path of the error instead.
+    pub fn from_node_error(source: &str, node: &Node, err: FromNodeError) -> Self {
+        let path = err.path().map(ToString::to_string);
+        let fallback = err.start_mark();
+        let message = err.into_inner().to_string();
+        let span = path
+            .as_deref()
+            .map_or_else(
+                || node_span(source, node, None),
+                |path| locate_path(source, node, path),
+            )
+            .or_else(|| fallback.map(Span::new_start))
+            .unwrap_or_else(Span::new_blank);
+        Self {
+            span,
+            path,
+            message,
+        }
+    }
+
+    /// The location of the error.
+    #[inline]
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
+
+    /// The path of the offending node (e.g., `jobs.build.steps[0].uses`), if known.
+    #[inline]
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_deref()
+    }
+
+    /// The description of the error, without location.
+    #[inline]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(start) = self.span.start() {
+            write!(f, "{}:{}: ", start.line(), start.column())?;
+        }
+        if let Some(path) = &self.path {
+            write!(f, "{path}: ")?;
+        }
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Splits a path like `jobs.build.steps[0].uses` into its segments.
+fn path_segments(path: &str) -> Vec<PathSegment<'_>> {
+    path.split('.')
+        .flat_map(|part| {
+            let (key, indices) = part.split_once('[').unwrap_or((part, ""));
+            let indices = indices
+                .split('[')
+                .filter_map(|index| index.trim_end_matches(']').parse().ok())
+                .map(PathSegment::Index);
+            (!key.is_empty())
+                .then_some(PathSegment::Key(key))
+                .into_iter()
+                .chain(indices)
+        })
+        .collect()
+}
+
+/// Returns the location of the node at the given path, or of its closest existing ancestor.
+fn locate_path(source: &str, node: &Node, path: &str) -> Option<Span> {
+    let mut current = node;
+    let mut key_span = None;
+    for segment in path_segments(path) {
+        let next = match segment {
+            PathSegment::Key(key) => current.as_mapping().and_then(|mapping| {
+                mapping
+                    .iter()
+                    .find(|(candidate, _value)| candidate.as_str() == key)
+                    .map(|(candidate, value)| (Some(*candidate.span()), value))
+            }),
+            PathSegment::Index(index) => current
+                .as_sequence()
+                .and_then(|sequence| sequence.get(index))
+                .map(|value| (None, value)),
+        };
+        let Some((next_key_span, value)) = next else {
+            break;
+        };
+        key_span = next_key_span;
+        current = value;
+    }
+    node_span(source, current, key_span)
+}
+
+/// Returns the location of a node, given the location of its key (if any).
+///
+/// The YAML loader records the start of block mappings and sequences after their first key or
+/// item, so the key of a mapping or sequence is used instead.
+pub(super) fn node_span(source: &str, node: &Node, key_span: Option<Span>) -> Option<Span> {
+    match node {
+        Node::Scalar(scalar) => {
+            let start = scalar.span().start()?;
+            return scalar_span(source, start, scalar.as_str())
+                .or_else(|| Some(Span::new_start(*start)));
+        }
+        Node::Mapping(mapping) => key_span.and_then(|span| span.start().copied()).or_else(|| {
+            mapping
+                .keys()
+                .next()
+                .and_then(|key| key.span().start().copied())
+        }),
+        Node::Sequence(sequence) => key_span.and_then(|span| span.start().copied()).or_else(|| {
+            sequence
+                .first()
+                .and_then(|item| node_span(source, item, None))
+                .and_then(|span| span.start().copied())
+        }),
+    }
+    .map(Span::new_start)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse_error(source: &str) -> ParseError {
+        let node = marked_yaml::parse_yaml(0, source).unwrap();
+        let err = marked_yaml::from_node::<crate::parser::workflow::Workflow>(&node).unwrap_err();
+        ParseError::from_node_error(source, &node, err)
+    }
+
+    #[test]
+    fn test_path_segments() {
+        assert_eq!(
+            path_segments("jobs.build.steps[0].uses"),
+            vec![
+                PathSegment::Key("jobs"),
+                PathSegment::Key("build"),
+                PathSegment::Key("steps"),
+                PathSegment::Index(0),
+                PathSegment::Key("uses"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let err = parse_error("on: push\njobs:\n  build:\n    runs-on: ubuntu-latest\n    steps:\n      - uses: actions/checkout\n");
+        assert_eq!(err.path(), Some("jobs.build.steps[0].uses"));
+        assert_eq!(err.span().end().map(|end| end.column()), Some(31));
+        assert_eq!(
+            err.to_string(),
+            "6:15: jobs.build.steps[0].uses: action reference \"actions/checkout\" is missing a version"
+        );
+
+        let err = parse_error("on: push\njobs:\n  build:\n    steps: []\n");
+        assert_eq!(err.to_string(), "3:3: jobs.build: missing field `runs-on`");
+
+        let err = marked_yaml::parse_yaml(0, "on: push\njobs: [\n").unwrap_err();
+        let err = ParseError::from_load_error(&err);
+        assert!(err.span().start().is_some());
+        assert!(!err.message().starts_with(char::is_numeric));
+    }
+}
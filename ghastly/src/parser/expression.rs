@@ -6,6 +6,9 @@
 //
 // SPDX-License-Identifier: MPL-2.0
 
+use std::ops::Range;
+use thiserror::Error;
+
 /// A Token consisting of a value and a token kind.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Token<'a> {
@@ -15,7 +18,6 @@ pub struct Token<'a> {
 
 impl Token<'_> {
     #[inline]
-    #[expect(dead_code)]
     pub fn value(&self) -> &str {
         self.value
     }
@@ -51,6 +53,26 @@ pub enum TokenKind {
     Expression,
 }
 
+/// Returns the byte offset of the `}}` that ends the expression at the start of `text`.
+///
+/// String literals are skipped, so that e.g. `format('}}')` does not end the expression. If a
+/// string literal is not terminated, the first `}}` ends the expression instead.
+fn find_expression_end(text: &str) -> Option<usize> {
+    let mut in_string = false;
+    let bytes = text.as_bytes();
+    (0..bytes.len())
+        .find(|&index| match bytes[index] {
+            // Escaped quotes (`''`) toggle twice, so they do not end the string literal.
+            b'\'' => {
+                in_string = !in_string;
+                false
+            }
+            b'}' => !in_string && bytes.get(index + 1) == Some(&b'}'),
+            _ => false,
+        })
+        .or_else(|| text.find("}}"))
+}
+
 /// Tokenize a string to differentiate normal strings from GitHub expressions.
 pub fn tokenize(text: &str) -> impl Iterator<Item = Token<'_>> + '_ {
     let mut remainder = text;
@@ -62,10 +84,10 @@ pub fn tokenize(text: &str) -> impl Iterator<Item = Token<'_>> + '_ {
         }
 
         if current_token_kind == TokenKind::Expression {
-            match remainder.split_once("}}") {
-                Some((before, after)) => {
-                    let value = Token::expression(before);
-                    remainder = after;
+            match find_expression_end(remainder) {
+                Some(end) => {
+                    let value = Token::expression(&remainder[..end]);
+                    remainder = &remainder[end + "}}".len()..];
                     current_token_kind = TokenKind::String;
                     Some(value)
                 }
@@ -97,6 +119,619 @@ pub fn tokenize(text: &str) -> impl Iterator<Item = Token<'_>> + '_ {
     })
 }
 
+/// Parse all GitHub expressions embedded in the given text.
+///
+/// The spans of the resulting expressions are byte offsets into `text` (not into the expression
+/// itself), so that they can be mapped back into the YAML source.
+pub fn parse_template(text: &str) -> impl Iterator<Item = Result<Expr, ParseError>> + '_ {
+    let mut offset = 0;
+    tokenize(text).filter_map(move |token| {
+        let start = offset;
+        offset += token.value().len();
+        match token.kind() {
+            TokenKind::String => {
+                offset += "${{".len();
+                None
+            }
+            TokenKind::Expression => {
+                offset += "}}".len();
+                Some(Parser::new(token.value(), start).parse())
+            }
+        }
+    })
+}
+
+/// Parse a single GitHub expression, with or without the surrounding `${{` and `}}`.
+///
+/// Unlike [`parse_template`], the text must consist of exactly one expression (e.g. the contents
+/// of a template expression). The spans of the resulting expression are byte offsets into `text`.
+pub fn parse_expression(text: &str) -> Result<Expr, ParseError> {
+    let trimmed = text.trim();
+    let start = text.len() - text.trim_start().len();
+    let (inner, offset) = match trimmed.strip_prefix("${{") {
+        Some(inner) => (
+            inner.strip_suffix("}}").unwrap_or(inner),
+            start + "${{".len(),
+        ),
+        None => (trimmed, start),
+    };
+    Parser::new(inner, offset).parse()
+}
+
+/// An error that occurred while parsing an expression.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("{message}")]
+pub struct ParseError {
+    message: String,
+    span: Range<usize>,
+}
+
+impl ParseError {
+    fn new(message: impl Into<String>, span: Range<usize>) -> Self {
+        Self {
+            message: message.into(),
+            span,
+        }
+    }
+
+    /// The byte range that caused the error.
+    #[inline]
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+}
+
+/// A node of the expression syntax tree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expr {
+    /// The kind of expression.
+    pub kind: ExprKind,
+    /// The byte range of the expression in the parsed text.
+    pub span: Range<usize>,
+}
+
+/// The kind of an expression node.
+///
+/// Documentation: <https://docs.github.com/en/actions/writing-workflows/choosing-what-your-workflow-does/evaluate-expressions-in-workflows-and-actions>
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExprKind {
+    /// A literal value, e.g. `'foo'`, `42` or `true`.
+    Literal(Literal),
+    /// A named context, e.g. `github` or `matrix`.
+    Context(String),
+    /// Property dereference, e.g. `github.event`.
+    Property { object: Box<Expr>, name: String },
+    /// Index access, e.g. `github['event']`.
+    Index { object: Box<Expr>, index: Box<Expr> },
+    /// Object filter, e.g. `github.event.commits.*` or `labels[*]`.
+    Filter { object: Box<Expr> },
+    /// Function call, e.g. `contains(github.ref, 'main')`.
+    Call { function: String, args: Vec<Expr> },
+    /// Logical negation, e.g. `!cancelled()`.
+    Not { operand: Box<Expr> },
+    /// Binary operation, e.g. `a && b` or `a == b`.
+    Binary {
+        op: BinaryOp,
+        lhs: Box<Expr>,
+        rhs: Box<Expr>,
+    },
+}
+
+/// A literal value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    Null,
+    Boolean(bool),
+    Number(f64),
+    String(String),
+}
+
+/// A binary operator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    /// `&&`
+    And,
+    /// `||`
+    Or,
+    /// `==`
+    Eq,
+    /// `!=`
+    Ne,
+    /// `<`
+    Lt,
+    /// `<=`
+    Le,
+    /// `>`
+    Gt,
+    /// `>=`
+    Ge,
+}
+
+impl Expr {
+    /// Returns the direct children of this expression.
+    pub fn children(&self) -> Vec<&Expr> {
+        match &self.kind {
+            ExprKind::Literal(_) | ExprKind::Context(_) => vec![],
+            ExprKind::Property { object, .. } | ExprKind::Filter { object } => vec![object],
+            ExprKind::Index { object, index } => vec![object, index],
+            ExprKind::Call { args, .. } => args.iter().collect(),
+            ExprKind::Not { operand } => vec![operand],
+            ExprKind::Binary { lhs, rhs, .. } => vec![lhs, rhs],
+        }
+    }
+
+    /// Iterates over this expression and all of its descendants (in pre-order).
+    pub fn iter(&self) -> impl Iterator<Item = &Expr> {
+        let mut stack = vec![self];
+        std::iter::from_fn(move || {
+            let expr = stack.pop()?;
+            stack.extend(expr.children().into_iter().rev());
+            Some(expr)
+        })
+    }
+
+    /// Returns the context path of a property dereference chain, e.g.
+    /// `github.event.pull_request.title` for `github.event['pull_request'].title`.
+    ///
+    /// Object filters are represented as `*`, numeric indices as `[N]`. Returns `None` if the
+    /// expression is not a pure context access (e.g. a function call or a computed index).
+    pub fn context_path(&self) -> Option<String> {
+        match &self.kind {
+            ExprKind::Context(name) => Some(name.to_lowercase()),
+            ExprKind::Property { object, name } => object
+                .context_path()
+                .map(|path| format!("{path}.{}", name.to_lowercase())),
+            ExprKind::Filter { object } => object.context_path().map(|path| format!("{path}.*")),
+            ExprKind::Index { object, index } => {
+                let path = object.context_path()?;
+                match &index.kind {
+                    ExprKind::Literal(Literal::String(name)) => {
+                        Some(format!("{path}.{}", name.to_lowercase()))
+                    }
+                    ExprKind::Literal(Literal::Number(number)) => Some(format!("{path}[{number}]")),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A lexical token of an expression.
+#[derive(Debug, Clone, PartialEq)]
+enum ExprToken {
+    Identifier(String),
+    Literal(Literal),
+    Dot,
+    Comma,
+    Star,
+    Not,
+    LeftParen,
+    RightParen,
+    LeftBracket,
+    RightBracket,
+    Operator(BinaryOp),
+}
+
+/// The maximum nesting depth of an expression, which prevents deeply nested expressions (e.g. in
+/// workflows of an attacker) from overflowing the stack, even on the 2 MiB stack of a thread.
+const MAX_DEPTH: usize = 128;
+
+/// Recursive descent parser for GitHub expressions.
+struct Parser {
+    tokens: Vec<(ExprToken, Range<usize>)>,
+    position: usize,
+    end: usize,
+    /// The depth of the expression that is being parsed, which also counts operands of chained
+    /// operators and properties (because they are nested in the resulting tree).
+    depth: usize,
+    error: Option<ParseError>,
+}
+
+impl Parser {
+    fn new(text: &str, offset: usize) -> Self {
+        let end = offset + text.len();
+        match lex(text, offset) {
+            Ok(tokens) => Self {
+                tokens,
+                position: 0,
+                end,
+                depth: 0,
+                error: None,
+            },
+            Err(error) => Self {
+                tokens: vec![],
+                position: 0,
+                end,
+                depth: 0,
+                error: Some(error),
+            },
+        }
+    }
+
+    fn parse(mut self) -> Result<Expr, ParseError> {
+        if let Some(error) = self.error.take() {
+            return Err(error);
+        }
+        let expr = self.parse_or()?;
+        match self.tokens.get(self.position) {
+            None => Ok(expr),
+            Some((token, span)) => Err(ParseError::new(
+                format!("unexpected token {token:?}"),
+                span.clone(),
+            )),
+        }
+    }
+
+    fn peek(&self) -> Option<&ExprToken> {
+        self.tokens.get(self.position).map(|(token, _)| token)
+    }
+
+    fn next(&mut self) -> Result<(ExprToken, Range<usize>), ParseError> {
+        let token =
+            self.tokens.get(self.position).cloned().ok_or_else(|| {
+                ParseError::new("unexpected end of expression", self.end..self.end)
+            })?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, expected: ExprToken) -> Result<Range<usize>, ParseError> {
+        let (token, span) = self.next()?;
+        if token == expected {
+            Ok(span)
+        } else {
+            Err(ParseError::new(
+                format!("expected {expected:?}, found {token:?}"),
+                span,
+            ))
+        }
+    }
+
+    /// Increases the depth, or returns an error at the given token if the expression is nested
+    /// too deeply.
+    fn nest(&mut self, span: &Range<usize>) -> Result<(), ParseError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(ParseError::new(
+                format!("expression is nested more than {MAX_DEPTH} levels deep"),
+                span.clone(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn binary(lhs: Expr, op: BinaryOp, rhs: Expr) -> Expr {
+        Expr {
+            span: lhs.span.start..rhs.span.end,
+            kind: ExprKind::Binary {
+                op,
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+            },
+        }
+    }
+
+    fn parse_binary(
+        &mut self,
+        operators: &[BinaryOp],
+        operand: fn(&mut Self) -> Result<Expr, ParseError>,
+    ) -> Result<Expr, ParseError> {
+        let depth = self.depth;
+        let mut lhs = operand(self)?;
+        while let Some(ExprToken::Operator(op)) = self.peek() {
+            let op = *op;
+            if !operators.contains(&op) {
+                break;
+            }
+            let (_, span) = self.next()?;
+            self.nest(&span)?;
+            let rhs = operand(self)?;
+            lhs = Self::binary(lhs, op, rhs);
+        }
+        self.depth = depth;
+        Ok(lhs)
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, ParseError> {
+        self.parse_binary(&[BinaryOp::Or], Self::parse_and)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, ParseError> {
+        self.parse_binary(&[BinaryOp::And], Self::parse_equality)
+    }
+
+    fn parse_equality(&mut self) -> Result<Expr, ParseError> {
+        self.parse_binary(&[BinaryOp::Eq, BinaryOp::Ne], Self::parse_comparison)
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expr, ParseError> {
+        self.parse_binary(
+            &[BinaryOp::Lt, BinaryOp::Le, BinaryOp::Gt, BinaryOp::Ge],
+            Self::parse_unary,
+        )
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, ParseError> {
+        if self.peek() == Some(&ExprToken::Not) {
+            let (_, span) = self.next()?;
+            self.nest(&span)?;
+            let operand = self.parse_unary()?;
+            self.depth -= 1;
+            return Ok(Expr {
+                span: span.start..operand.span.end,
+                kind: ExprKind::Not {
+                    operand: Box::new(operand),
+                },
+            });
+        }
+        self.parse_postfix()
+    }
+
+    fn parse_postfix(&mut self) -> Result<Expr, ParseError> {
+        let depth = self.depth;
+        let mut expr = self.parse_primary()?;
+        loop {
+            match self.peek() {
+                Some(ExprToken::Dot) => {
+                    let (_, dot_span) = self.next()?;
+                    self.nest(&dot_span)?;
+                    let (token, span) = self.next()?;
+                    let start = expr.span.start;
+                    expr = match token {
+                        ExprToken::Star => Expr {
+                            span: start..span.end,
+                            kind: ExprKind::Filter {
+                                object: Box::new(expr),
+                            },
+                        },
+                        ExprToken::Identifier(name) => Expr {
+                            span: start..span.end,
+                            kind: ExprKind::Property {
+                                object: Box::new(expr),
+                                name,
+                            },
+                        },
+                        // Keywords are valid property names, e.g. `inputs.true`.
+                        ExprToken::Literal(Literal::Boolean(value)) => Expr {
+                            span: start..span.end,
+                            kind: ExprKind::Property {
+                                object: Box::new(expr),
+                                name: value.to_string(),
+                            },
+                        },
+                        ExprToken::Literal(Literal::Null) => Expr {
+                            span: start..span.end,
+                            kind: ExprKind::Property {
+                                object: Box::new(expr),
+                                name: "null".to_string(),
+                            },
+                        },
+                        other => {
+                            return Err(ParseError::new(
+                                format!("expected property name, found {other:?}"),
+                                span,
+                            ))
+                        }
+                    };
+                }
+                Some(ExprToken::LeftBracket) => {
+                    let (_, bracket_span) = self.next()?;
+                    self.nest(&bracket_span)?;
+                    let start = expr.span.start;
+                    if self.peek() == Some(&ExprToken::Star) {
+                        self.position += 1;
+                        let end = self.expect(ExprToken::RightBracket)?.end;
+                        expr = Expr {
+                            span: start..end,
+                            kind: ExprKind::Filter {
+                                object: Box::new(expr),
+                            },
+                        };
+                    } else {
+                        let index = self.parse_or()?;
+                        let end = self.expect(ExprToken::RightBracket)?.end;
+                        expr = Expr {
+                            span: start..end,
+                            kind: ExprKind::Index {
+                                object: Box::new(expr),
+                                index: Box::new(index),
+                            },
+                        };
+                    }
+                }
+                _ => {
+                    self.depth = depth;
+                    return Ok(expr);
+                }
+            }
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, ParseError> {
+        let (token, span) = self.next()?;
+        match token {
+            ExprToken::Literal(literal) => Ok(Expr {
+                kind: ExprKind::Literal(literal),
+                span,
+            }),
+            ExprToken::LeftParen => {
+                self.nest(&span)?;
+                let expr = self.parse_or()?;
+                let end = self.expect(ExprToken::RightParen)?.end;
+                self.depth -= 1;
+                Ok(Expr {
+                    kind: expr.kind,
+                    span: span.start..end,
+                })
+            }
+            ExprToken::Identifier(name) if self.peek() == Some(&ExprToken::LeftParen) => {
+                self.position += 1;
+                self.nest(&span)?;
+                let mut args = vec![];
+                if self.peek() != Some(&ExprToken::RightParen) {
+                    loop {
+                        args.push(self.parse_or()?);
+                        if self.peek() == Some(&ExprToken::Comma) {
+                            self.position += 1;
+                        } else {
+                            break;
+                        }
+                    }
+                }
+                let end = self.expect(ExprToken::RightParen)?.end;
+                self.depth -= 1;
+                Ok(Expr {
+                    kind: ExprKind::Call {
+                        function: name,
+                        args,
+                    },
+                    span: span.start..end,
+                })
+            }
+            ExprToken::Identifier(name) => Ok(Expr {
+                kind: ExprKind::Context(name),
+                span,
+            }),
+            other => Err(ParseError::new(format!("unexpected token {other:?}"), span)),
+        }
+    }
+}
+
+/// Split an expression into tokens. Spans are shifted by `offset`.
+fn lex(text: &str, offset: usize) -> Result<Vec<(ExprToken, Range<usize>)>, ParseError> {
+    let mut tokens = vec![];
+    let mut chars = text.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let mut end = start + c.len_utf8();
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '.' if !chars.peek().is_some_and(|(_, next)| next.is_ascii_digit()) => ExprToken::Dot,
+            ',' => ExprToken::Comma,
+            '*' => ExprToken::Star,
+            '(' => ExprToken::LeftParen,
+            ')' => ExprToken::RightParen,
+            '[' => ExprToken::LeftBracket,
+            ']' => ExprToken::RightBracket,
+            '!' | '=' | '<' | '>' => {
+                let followed_by_eq = chars.next_if(|(_, next)| *next == '=').is_some();
+                if followed_by_eq {
+                    end += 1;
+                }
+                match (c, followed_by_eq) {
+                    ('!', false) => ExprToken::Not,
+                    ('!', true) => ExprToken::Operator(BinaryOp::Ne),
+                    ('=', true) => ExprToken::Operator(BinaryOp::Eq),
+                    ('<', false) => ExprToken::Operator(BinaryOp::Lt),
+                    ('<', true) => ExprToken::Operator(BinaryOp::Le),
+                    ('>', false) => ExprToken::Operator(BinaryOp::Gt),
+                    ('>', true) => ExprToken::Operator(BinaryOp::Ge),
+                    _ => {
+                        return Err(ParseError::new(
+                            "expected '==' operator",
+                            offset + start..offset + end,
+                        ))
+                    }
+                }
+            }
+            '&' | '|' => {
+                if chars.next_if(|(_, next)| *next == c).is_none() {
+                    return Err(ParseError::new(
+                        format!("expected '{c}{c}' operator"),
+                        offset + start..offset + end,
+                    ));
+                }
+                end += 1;
+                if c == '&' {
+                    ExprToken::Operator(BinaryOp::And)
+                } else {
+                    ExprToken::Operator(BinaryOp::Or)
+                }
+            }
+            '\'' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some((index, '\'')) => {
+                            end = index + 1;
+                            // Single quotes are escaped by doubling them.
+                            if chars.next_if(|(_, next)| *next == '\'').is_some() {
+                                value.push('\'');
+                            } else {
+                                break;
+                            }
+                        }
+                        Some((_, c)) => value.push(c),
+                        None => {
+                            return Err(ParseError::new(
+                                "unterminated string literal",
+                                offset + start..offset + text.len(),
+                            ))
+                        }
+                    }
+                }
+                ExprToken::Literal(Literal::String(value))
+            }
+            c if c.is_ascii_digit() || c == '.' || c == '-' => {
+                // The sign of an exponent (e.g. `1e-5`) is part of the literal.
+                let is_hex = text[start..].trim_start_matches('-').starts_with("0x");
+                let mut previous = c;
+                while let Some((index, next)) = chars.next_if(|(_, next)| {
+                    next.is_ascii_alphanumeric()
+                        || *next == '.'
+                        || (matches!(next, '+' | '-') && matches!(previous, 'e' | 'E') && !is_hex)
+                }) {
+                    end = index + next.len_utf8();
+                    previous = next;
+                }
+                let literal = &text[start..end];
+                let (sign, digits) = match literal.strip_prefix('-') {
+                    Some(digits) => (-1.0, digits),
+                    None => (1.0, literal),
+                };
+                let number = match digits.strip_prefix("0x") {
+                    Some(hex) => i64::from_str_radix(hex, 16).ok().map(|value| value as f64),
+                    // Rust also accepts e.g. `inf` and `NaN`, which are not valid literals.
+                    None => digits
+                        .chars()
+                        .all(|c| c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E' | '+' | '-'))
+                        .then(|| digits.parse::<f64>().ok())
+                        .flatten(),
+                };
+                let number = number.map(|number| sign * number);
+                let number = number.ok_or_else(|| {
+                    ParseError::new(
+                        format!("invalid number literal {literal:?}"),
+                        offset + start..offset + end,
+                    )
+                })?;
+                ExprToken::Literal(Literal::Number(number))
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                while let Some((index, next)) = chars
+                    .next_if(|(_, next)| next.is_ascii_alphanumeric() || matches!(next, '_' | '-'))
+                {
+                    end = index + next.len_utf8();
+                }
+                match &text[start..end] {
+                    "true" => ExprToken::Literal(Literal::Boolean(true)),
+                    "false" => ExprToken::Literal(Literal::Boolean(false)),
+                    "null" => ExprToken::Literal(Literal::Null),
+                    identifier => ExprToken::Identifier(identifier.to_owned()),
+                }
+            }
+            other => {
+                return Err(ParseError::new(
+                    format!("unexpected character {other:?}"),
+                    offset + start..offset + end,
+                ))
+            }
+        };
+        tokens.push((token, offset + start..offset + end));
+    }
+    Ok(tokens)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -133,5 +768,167 @@ mod test {
                 Token::string("'!")
             ]
         );
+        // The expression does not end inside of string literals.
+        assert_eq!(
+            tokenize("${{ format('}}{0}', 'it''s }}') }}!").collect::<Vec<_>>(),
+            vec![
+                Token::string(""),
+                Token::expression(" format('}}{0}', 'it''s }}') "),
+                Token::string("!")
+            ]
+        );
+        // Unless the string literal is not terminated.
+        assert_eq!(
+            tokenize("${{ it's }} x").collect::<Vec<_>>(),
+            vec![
+                Token::string(""),
+                Token::expression(" it's "),
+                Token::string(" x")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_template_spans() {
+        let text = "echo ${{ github.event.pull_request.title }} and ${{ matrix.os }}";
+        let exprs: Vec<_> = parse_template(text).map(Result::unwrap).collect();
+        assert_eq!(exprs.len(), 2);
+        assert_eq!(
+            &text[exprs[0].span.clone()],
+            "github.event.pull_request.title"
+        );
+        assert_eq!(
+            exprs[0].context_path().as_deref(),
+            Some("github.event.pull_request.title")
+        );
+        assert_eq!(&text[exprs[1].span.clone()], "matrix.os");
+
+        let text = "echo ${{ format('}}{0}', github.head_ref) }}";
+        let exprs: Vec<_> = parse_template(text).map(Result::unwrap).collect();
+        assert_eq!(exprs.len(), 1);
+        assert_eq!(
+            &text[exprs[0].span.clone()],
+            "format('}}{0}', github.head_ref)"
+        );
+    }
+
+    #[test]
+    fn test_parse_expression() {
+        let expr = parse_expression(
+            "!cancelled() && (github.event['pull_request'].head.ref == 'main' || 0x10 >= 1.5e1)",
+        )
+        .unwrap();
+        let ExprKind::Binary { op, lhs, rhs } = &expr.kind else {
+            panic!("expected binary expression, got {expr:?}");
+        };
+        assert_eq!(*op, BinaryOp::And);
+        assert!(matches!(lhs.kind, ExprKind::Not { .. }));
+        let ExprKind::Binary { op, lhs, .. } = &rhs.kind else {
+            panic!("expected binary expression, got {rhs:?}");
+        };
+        assert_eq!(*op, BinaryOp::Or);
+        let ExprKind::Binary { op, lhs, rhs } = &lhs.kind else {
+            panic!("expected binary expression, got {lhs:?}");
+        };
+        assert_eq!(*op, BinaryOp::Eq);
+        assert_eq!(
+            lhs.context_path().as_deref(),
+            Some("github.event.pull_request.head.ref")
+        );
+        assert_eq!(rhs.kind, ExprKind::Literal(Literal::String("main".into())));
+    }
+
+    #[test]
+    fn test_parse_filters_and_calls() {
+        let expr =
+            parse_expression("${{ contains(github.event.commits.*.message, 'it''s') }}").unwrap();
+        let ExprKind::Call { function, args } = &expr.kind else {
+            panic!("expected function call, got {expr:?}");
+        };
+        assert_eq!(function, "contains");
+        assert_eq!(
+            args[0].context_path().as_deref(),
+            Some("github.event.commits.*.message")
+        );
+        assert_eq!(
+            args[1].kind,
+            ExprKind::Literal(Literal::String("it's".into()))
+        );
+        assert_eq!(
+            expr.iter()
+                .filter(|expr| matches!(expr.kind, ExprKind::Filter { .. }))
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse_expression("github.").unwrap_err().span(), 7..7);
+        assert_eq!(parse_expression("a & b").unwrap_err().span(), 2..3);
+        assert_eq!(parse_expression("'unterminated").unwrap_err().span(), 0..13);
+        assert_eq!(parse_expression("foo(a, b").unwrap_err().span(), 8..8);
+    }
+
+    #[test]
+    fn test_parse_nesting_depth() {
+        let nested = |open: &str, close: &str, depth: usize| {
+            format!("{}a{}", open.repeat(depth), close.repeat(depth))
+        };
+        assert!(parse_expression(&nested("(", ")", MAX_DEPTH)).is_ok());
+        assert_eq!(
+            parse_expression(&nested("(", ")", MAX_DEPTH + 1))
+                .unwrap_err()
+                .span(),
+            MAX_DEPTH..MAX_DEPTH + 1
+        );
+        for expression in [
+            nested("(", ")", 20_000),
+            nested("!", "", 20_000),
+            nested("f(", ")", 20_000),
+            nested("a[", "]", 20_000),
+            vec!["a"; 20_000].join(" || "),
+            vec!["a"; 20_000].join("."),
+        ] {
+            assert!(parse_expression(&expression).is_err());
+        }
+    }
+
+    fn number(text: &str) -> Result<f64, ParseError> {
+        match lex(text, 0)?.as_slice() {
+            [(ExprToken::Literal(Literal::Number(number)), span)] if *span == (0..text.len()) => {
+                Ok(*number)
+            }
+            tokens => panic!("expected a single number literal, got {tokens:?}"),
+        }
+    }
+
+    #[test]
+    fn test_lex_numbers() {
+        assert_eq!(number("42"), Ok(42.0));
+        assert_eq!(number("-1.5"), Ok(-1.5));
+        assert_eq!(number(".5"), Ok(0.5));
+        assert_eq!(number("1e-5"), Ok(1e-5));
+        assert_eq!(number("2.5E+3"), Ok(2500.0));
+        assert_eq!(number("-1e5"), Ok(-100000.0));
+        assert_eq!(number("0xff"), Ok(255.0));
+        assert_eq!(number("0xFF"), Ok(255.0));
+        assert_eq!(number("-0x10"), Ok(-16.0));
+        assert!(lex("0xg", 0).is_err());
+        assert!(lex("1e", 0).is_err());
+        assert!(lex("-inf", 0).is_err());
+        // Hexadecimal literals have no exponent.
+        assert_eq!(lex("0x1e-5", 0).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_parse_expression_spans() {
+        let text = "  ${{ github.head_ref }}";
+        let expr = parse_expression(text).unwrap();
+        assert_eq!(&text[expr.span.clone()], "github.head_ref");
+
+        let text = "\tgithub.event_name == 'push'";
+        let expr = parse_expression(text).unwrap();
+        assert_eq!(&text[expr.span.clone()], "github.event_name == 'push'");
     }
 }
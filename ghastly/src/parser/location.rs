@@ -0,0 +1,182 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+use marked_yaml::types::MarkedMappingNode;
+use marked_yaml::{Marker, Span, Spanned};
+use std::ops::Range;
+
+/// Maps a byte range inside the value of a scalar back to its location in the YAML source.
+///
+/// The YAML loader only records where a scalar starts, so the location is reconstructed from
+/// the scalar's value. This is exact for plain and quoted single-line scalars as well as literal
+/// block scalars (`|`). For folded and multi-line flow scalars, the result is a best-effort
+/// approximation, because line breaks in the source do not correspond to line breaks in the
+/// value.
+pub fn locate(source: &str, scalar: &Spanned<String>, range: Range<usize>) -> Span {
+    let Some(start) = scalar.span().start() else {
+        return *scalar.span();
+    };
+
+    // Quoted scalars start at the quote character, so the value starts one column later.
+    let is_quoted = source
+        .lines()
+        .nth(start.line().saturating_sub(1))
+        .and_then(|line| line.chars().nth(start.column().saturating_sub(1)))
+        .is_some_and(|c| c == '"' || c == '\'');
+    let first_line_offset = if is_quoted { 1 } else { 0 };
+
+    let position = |offset: usize| {
+        let prefix = scalar.get(..offset).unwrap_or(scalar.as_str());
+        let line_index = prefix.matches('\n').count();
+        let column_index = prefix
+            .rsplit_once('\n')
+            .map_or(prefix, |(_, last_line)| last_line)
+            .chars()
+            .count();
+        if line_index == 0 {
+            Marker::new(
+                start.source(),
+                start.line(),
+                start.column() + first_line_offset + column_index,
+            )
+        } else {
+            // Subsequent lines of block scalars share the indentation of the first line.
+            Marker::new(
+                start.source(),
+                start.line() + line_index,
+                start.column() + column_index,
+            )
+        }
+    };
+
+    Span::new_with_marks(position(range.start), position(range.end))
+}
+
+/// Converts a location (with 1-based line and column) into a byte offset into the YAML source.
+///
+/// Returns `None` if the location is outside of the source.
+pub fn offset(source: &str, marker: &Marker) -> Option<usize> {
+    let mut line_start = 0;
+    for _ in 1..marker.line() {
+        line_start += source[line_start..].find('\n')? + 1;
+    }
+    let line = source[line_start..].split('\n').next().unwrap_or_default();
+    let column_offset = match line.char_indices().nth(marker.column().checked_sub(1)?) {
+        Some((column_offset, _)) => column_offset,
+        // The location may point to the end of the line.
+        None if line.chars().count() + 1 == marker.column() => line.len(),
+        None => return None,
+    };
+    Some(line_start + column_offset)
+}
+
+/// Returns the location of a single-line scalar that starts at the given location, if its source
+/// text is the value in plain, single-quoted or double-quoted style.
+///
+/// This is needed to replace scalars, because the YAML loader does not record where they end.
+pub fn scalar_span(source: &str, start: &Marker, value: &str) -> Option<Span> {
+    let text = &source[offset(source, start)?..];
+    let length = [
+        value.to_owned(),
+        format!("'{value}'"),
+        format!("\"{value}\""),
+    ]
+    .into_iter()
+    .find(|candidate| {
+        text.strip_prefix(candidate.as_str()).is_some_and(|rest| {
+            rest.is_empty() || rest.starts_with([' ', '\t', '\r', '\n', ',', '}', ']'])
+        })
+    })?
+    .chars()
+    .count();
+    let end = Marker::new(start.source(), start.line(), start.column() + length);
+    Some(Span::new_with_marks(*start, end))
+}
+
+/// Returns the location of a scalar with the given value, including its end if it is a
+/// single-line scalar (see [`scalar_span`]), or the location recorded by the YAML loader (which
+/// only contains the start) otherwise.
+pub fn scalar_location<T>(source: &str, scalar: &Spanned<T>, value: &str) -> Span {
+    scalar
+        .span()
+        .start()
+        .and_then(|start| scalar_span(source, start, value))
+        .unwrap_or(*scalar.span())
+}
+
+/// Returns the indentation (in columns) of a block mapping key at the given location.
+///
+/// Sequence indicators (`- `) in front of the key count as indentation. Returns `None` if there
+/// is anything else in front of the key (e.g. because it is part of a flow mapping).
+pub fn indentation(source: &str, marker: &Marker) -> Option<usize> {
+    let start = offset(source, marker)?;
+    let line_start = source[..start].rfind('\n').map_or(0, |index| index + 1);
+    source[line_start..start]
+        .chars()
+        .all(|c| c == ' ' || c == '-')
+        .then(|| marker.column() - 1)
+}
+
+/// Returns the location of the given key in the mapping.
+pub fn key_start<'a>(mapping: &'a MarkedMappingNode, key: &str) -> Option<&'a Marker> {
+    mapping
+        .keys()
+        .find(|candidate| candidate.as_str() == key)
+        .and_then(|key| key.span().start())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    struct Scalars {
+        plain: Spanned<String>,
+        quoted: Spanned<String>,
+        block: Spanned<String>,
+    }
+
+    fn marks(span: Span) -> ((usize, usize), (usize, usize)) {
+        let start = span.start().unwrap();
+        let end = span.end().unwrap();
+        ((start.line(), start.column()), (end.line(), end.column()))
+    }
+
+    #[test]
+    fn test_locate() {
+        let source = "plain: a ${{ b }}\nquoted: \"a ${{ b }}\"\nblock: |\n  echo\n    ${{ b }}\n";
+        let scalars: Scalars = marked_yaml::from_yaml(0, source).unwrap();
+        assert_eq!(
+            marks(locate(source, &scalars.plain, 2..10)),
+            ((1, 10), (1, 18))
+        );
+        assert_eq!(
+            marks(locate(source, &scalars.quoted, 2..10)),
+            ((2, 12), (2, 20))
+        );
+        assert_eq!(
+            marks(locate(source, &scalars.block, 7..15)),
+            ((5, 5), (5, 13))
+        );
+    }
+
+    #[test]
+    fn test_offset() {
+        let source = "a: b\nc: \"ä\"\n";
+        assert_eq!(offset(source, &Marker::new(0, 1, 1)), Some(0));
+        assert_eq!(offset(source, &Marker::new(0, 2, 5)), Some(9));
+        assert_eq!(offset(source, &Marker::new(0, 2, 7)), Some(12));
+        assert_eq!(offset(source, &Marker::new(0, 2, 8)), None);
+        assert_eq!(offset(source, &Marker::new(0, 4, 1)), None);
+
+        let span = scalar_span(source, &Marker::new(0, 2, 4), "ä").unwrap();
+        assert_eq!(span.end().unwrap().column(), 7);
+        assert!(scalar_span(source, &Marker::new(0, 1, 4), "bc").is_none());
+    }
+}
@@ -6,5 +6,14 @@
 //
 // SPDX-License-Identifier: MPL-2.0
 
+pub mod action;
+pub mod action_ref;
+pub mod document;
+pub mod error;
 pub mod expression;
+pub mod location;
+pub mod schema;
+pub mod source;
+pub mod trigger;
+pub mod value;
 pub mod workflow;
@@ -0,0 +1,812 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Validation of workflows against the workflow syntax.
+//!
+//! The deserializer ignores unknown keys, so a misspelled key (e.g. `permisions`) is silently
+//! treated as if it was not set. The schema in this module follows the [official workflow
+//! syntax][syntax] and is used to report such keys, as well as values of the wrong type.
+//!
+//! [syntax]: https://docs.github.com/en/actions/writing-workflows/workflow-syntax-for-github-actions
+
+use super::error::{node_span, ParseError};
+use super::location::scalar_span;
+use super::value::{Scalar, ValueOrExpression};
+use marked_yaml::{Node, Span};
+
+/// The expected structure of a YAML node.
+#[derive(Debug)]
+pub enum Schema {
+    /// Any value.
+    Any,
+    /// A scalar, e.g. a string, number, boolean or expression.
+    Scalar,
+    /// A boolean or an expression.
+    Boolean,
+    /// A number or an expression.
+    Number,
+    /// A non-negative integer or an expression.
+    Integer,
+    /// One of the given scalars.
+    Enum(&'static [&'static str]),
+    /// A sequence of values.
+    Sequence(&'static Schema),
+    /// A mapping with arbitrary keys.
+    Map(&'static Schema),
+    /// A mapping with a fixed set of keys.
+    Mapping(&'static MappingSchema),
+    /// One of the given schemas. The first schema that matches the type of the node is used.
+    Either(&'static [&'static Schema]),
+}
+
+/// The keys of a mapping.
+#[derive(Debug)]
+pub struct MappingSchema {
+    /// The allowed keys and the schemas of their values.
+    pub keys: &'static [(&'static str, &'static Schema)],
+    /// Keys that are required.
+    pub required: &'static [&'static str],
+    /// Keys of which exactly one is required.
+    pub one_of: &'static [&'static str],
+    /// Pairs of keys that cannot be used together.
+    pub exclusive: &'static [(&'static str, &'static str)],
+}
+
+impl MappingSchema {
+    /// A mapping without required or exclusive keys.
+    const UNCONSTRAINED: Self = Self {
+        keys: &[],
+        required: &[],
+        one_of: &[],
+        exclusive: &[],
+    };
+}
+
+/// Enum values are only listed in error messages if there are at most this many.
+const MAX_EXPECTED_VALUES: usize = 5;
+
+const STRING: Schema = Schema::Scalar;
+const STRINGS: Schema = Schema::Either(&[&STRING, &Schema::Sequence(&STRING)]);
+const STRING_MAP: Schema = Schema::Map(&STRING);
+/// A map of strings, which can also be set by an expression.
+const STRING_MAP_OR_EXPRESSION: Schema = Schema::Either(&[&STRING_MAP, &STRING]);
+
+/// The names of the events that can trigger a workflow.
+pub const EVENTS: &[&str] = &[
+    "branch_protection_rule",
+    "check_run",
+    "check_suite",
+    "create",
+    "delete",
+    "deployment",
+    "deployment_status",
+    "discussion",
+    "discussion_comment",
+    "fork",
+    "gollum",
+    "issue_comment",
+    "issues",
+    "label",
+    "merge_group",
+    "milestone",
+    "page_build",
+    "project",
+    "project_card",
+    "project_column",
+    "public",
+    "pull_request",
+    "pull_request_review",
+    "pull_request_review_comment",
+    "pull_request_target",
+    "push",
+    "registry_package",
+    "release",
+    "repository_dispatch",
+    "schedule",
+    "status",
+    "watch",
+    "workflow_call",
+    "workflow_dispatch",
+    "workflow_run",
+];
+
+// Events without configuration are written as `push:` (i.e., `null`), so all events can also be
+// scalars.
+const ACTIVITY_EVENT: Schema = Schema::Either(&[
+    &STRING,
+    &Schema::Mapping(&MappingSchema {
+        keys: &[("types", &STRINGS)],
+        ..MappingSchema::UNCONSTRAINED
+    }),
+]);
+
+const PUSH_EVENT: Schema = Schema::Either(&[
+    &STRING,
+    &Schema::Mapping(&MappingSchema {
+        keys: &[
+            ("branches", &STRINGS),
+            ("branches-ignore", &STRINGS),
+            ("tags", &STRINGS),
+            ("tags-ignore", &STRINGS),
+            ("paths", &STRINGS),
+            ("paths-ignore", &STRINGS),
+        ],
+        ..MappingSchema::UNCONSTRAINED
+    }),
+]);
+
+const PULL_REQUEST_EVENT: Schema = Schema::Either(&[
+    &STRING,
+    &Schema::Mapping(&MappingSchema {
+        keys: &[
+            ("types", &STRINGS),
+            ("branches", &STRINGS),
+            ("branches-ignore", &STRINGS),
+            ("paths", &STRINGS),
+            ("paths-ignore", &STRINGS),
+        ],
+        ..MappingSchema::UNCONSTRAINED
+    }),
+]);
+
+const WORKFLOW_RUN_EVENT: Schema = Schema::Either(&[
+    &STRING,
+    &Schema::Mapping(&MappingSchema {
+        keys: &[
+            ("types", &STRINGS),
+            ("workflows", &STRINGS),
+            ("branches", &STRINGS),
+            ("branches-ignore", &STRINGS),
+        ],
+        ..MappingSchema::UNCONSTRAINED
+    }),
+]);
+
+const SCHEDULE_EVENT: Schema = Schema::Sequence(&Schema::Mapping(&MappingSchema {
+    keys: &[("cron", &STRING)],
+    required: &["cron"],
+    ..MappingSchema::UNCONSTRAINED
+}));
+
+const WORKFLOW_INPUT: Schema = Schema::Mapping(&MappingSchema {
+    keys: &[
+        ("description", &STRING),
+        ("required", &STRING),
+        ("default", &STRING),
+        (
+            "type",
+            &Schema::Enum(&["boolean", "choice", "environment", "number", "string"]),
+        ),
+        ("options", &Schema::Sequence(&STRING)),
+    ],
+    ..MappingSchema::UNCONSTRAINED
+});
+
+const WORKFLOW_DISPATCH_EVENT: Schema = Schema::Either(&[
+    &STRING,
+    &Schema::Mapping(&MappingSchema {
+        keys: &[("inputs", &Schema::Map(&WORKFLOW_INPUT))],
+        ..MappingSchema::UNCONSTRAINED
+    }),
+]);
+
+const WORKFLOW_CALL_EVENT: Schema = Schema::Either(&[
+    &STRING,
+    &Schema::Mapping(&MappingSchema {
+        keys: &[
+            ("inputs", &Schema::Map(&WORKFLOW_INPUT)),
+            (
+                "outputs",
+                &Schema::Map(&Schema::Mapping(&MappingSchema {
+                    keys: &[("description", &STRING), ("value", &STRING)],
+                    required: &["value"],
+                    ..MappingSchema::UNCONSTRAINED
+                })),
+            ),
+            (
+                "secrets",
+                &Schema::Map(&Schema::Either(&[
+                    &STRING,
+                    &Schema::Mapping(&MappingSchema {
+                        keys: &[("description", &STRING), ("required", &STRING)],
+                        ..MappingSchema::UNCONSTRAINED
+                    }),
+                ])),
+            ),
+        ],
+        ..MappingSchema::UNCONSTRAINED
+    }),
+]);
+
+const ON: Schema = Schema::Either(&[
+    &Schema::Enum(EVENTS),
+    &Schema::Sequence(&Schema::Enum(EVENTS)),
+    &Schema::Mapping(&MappingSchema {
+        keys: &[
+            ("branch_protection_rule", &ACTIVITY_EVENT),
+            ("check_run", &ACTIVITY_EVENT),
+            ("check_suite", &ACTIVITY_EVENT),
+            ("create", &ACTIVITY_EVENT),
+            ("delete", &ACTIVITY_EVENT),
+            ("deployment", &ACTIVITY_EVENT),
+            ("deployment_status", &ACTIVITY_EVENT),
+            ("discussion", &ACTIVITY_EVENT),
+            ("discussion_comment", &ACTIVITY_EVENT),
+            ("fork", &ACTIVITY_EVENT),
+            ("gollum", &ACTIVITY_EVENT),
+            ("issue_comment", &ACTIVITY_EVENT),
+            ("issues", &ACTIVITY_EVENT),
+            ("label", &ACTIVITY_EVENT),
+            ("merge_group", &ACTIVITY_EVENT),
+            ("milestone", &ACTIVITY_EVENT),
+            ("page_build", &ACTIVITY_EVENT),
+            ("project", &ACTIVITY_EVENT),
+            ("project_card", &ACTIVITY_EVENT),
+            ("project_column", &ACTIVITY_EVENT),
+            ("public", &ACTIVITY_EVENT),
+            ("pull_request", &PULL_REQUEST_EVENT),
+            ("pull_request_review", &ACTIVITY_EVENT),
+            ("pull_request_review_comment", &ACTIVITY_EVENT),
+            ("pull_request_target", &PULL_REQUEST_EVENT),
+            ("push", &PUSH_EVENT),
+            ("registry_package", &ACTIVITY_EVENT),
+            ("release", &ACTIVITY_EVENT),
+            ("repository_dispatch", &ACTIVITY_EVENT),
+            ("schedule", &SCHEDULE_EVENT),
+            ("status", &ACTIVITY_EVENT),
+            ("watch", &ACTIVITY_EVENT),
+            ("workflow_call", &WORKFLOW_CALL_EVENT),
+            ("workflow_dispatch", &WORKFLOW_DISPATCH_EVENT),
+            ("workflow_run", &WORKFLOW_RUN_EVENT),
+        ],
+        ..MappingSchema::UNCONSTRAINED
+    }),
+]);
+
+const PERMISSION_LEVEL: Schema = Schema::Enum(&["read", "write", "none"]);
+
+/// Token permissions, either for the whole workflow or a single job.
+///
+/// Documentation: <https://docs.github.com/en/actions/writing-workflows/workflow-syntax-for-github-actions#permissions>
+const PERMISSIONS: Schema = Schema::Either(&[
+    &Schema::Enum(&["read-all", "write-all"]),
+    &Schema::Mapping(&MappingSchema {
+        keys: &[
+            ("actions", &PERMISSION_LEVEL),
+            ("attestations", &PERMISSION_LEVEL),
+            ("checks", &PERMISSION_LEVEL),
+            ("contents", &PERMISSION_LEVEL),
+            ("deployments", &PERMISSION_LEVEL),
+            ("discussions", &PERMISSION_LEVEL),
+            ("id-token", &PERMISSION_LEVEL),
+            ("issues", &PERMISSION_LEVEL),
+            ("models", &PERMISSION_LEVEL),
+            ("packages", &PERMISSION_LEVEL),
+            ("pages", &PERMISSION_LEVEL),
+            ("pull-requests", &PERMISSION_LEVEL),
+            ("repository-projects", &PERMISSION_LEVEL),
+            ("security-events", &PERMISSION_LEVEL),
+            ("statuses", &PERMISSION_LEVEL),
+        ],
+        ..MappingSchema::UNCONSTRAINED
+    }),
+]);
+
+const DEFAULTS: Schema = Schema::Mapping(&MappingSchema {
+    keys: &[(
+        "run",
+        &Schema::Mapping(&MappingSchema {
+            keys: &[("shell", &STRING), ("working-directory", &STRING)],
+            ..MappingSchema::UNCONSTRAINED
+        }),
+    )],
+    ..MappingSchema::UNCONSTRAINED
+});
+
+const CONCURRENCY: Schema = Schema::Either(&[
+    &STRING,
+    &Schema::Mapping(&MappingSchema {
+        keys: &[("group", &STRING), ("cancel-in-progress", &Schema::Boolean)],
+        required: &["group"],
+        ..MappingSchema::UNCONSTRAINED
+    }),
+]);
+
+/// Documentation: <https://docs.github.com/en/actions/writing-workflows/workflow-syntax-for-github-actions#jobsjob_idservices>
+const SERVICE: Schema = Schema::Mapping(&MappingSchema {
+    keys: &[
+        ("image", &STRING),
+        (
+            "credentials",
+            &Schema::Mapping(&MappingSchema {
+                keys: &[("username", &STRING), ("password", &STRING)],
+                ..MappingSchema::UNCONSTRAINED
+            }),
+        ),
+        ("env", &STRING_MAP_OR_EXPRESSION),
+        ("ports", &Schema::Sequence(&STRING)),
+        ("volumes", &Schema::Sequence(&STRING)),
+        ("options", &STRING),
+    ],
+    required: &["image"],
+    ..MappingSchema::UNCONSTRAINED
+});
+
+/// The image can also be given directly, e.g. `container: node:22`.
+const CONTAINER: Schema = Schema::Either(&[&STRING, &SERVICE]);
+
+/// Documentation: <https://docs.github.com/en/actions/writing-workflows/workflow-syntax-for-github-actions#jobsjob_idsteps>
+const STEP: Schema = Schema::Mapping(&MappingSchema {
+    keys: &[
+        ("id", &STRING),
+        ("if", &STRING),
+        ("name", &STRING),
+        ("uses", &STRING),
+        ("run", &STRING),
+        ("working-directory", &STRING),
+        ("shell", &STRING),
+        ("with", &STRING_MAP_OR_EXPRESSION),
+        ("env", &STRING_MAP_OR_EXPRESSION),
+        ("continue-on-error", &Schema::Boolean),
+        ("timeout-minutes", &Schema::Number),
+    ],
+    one_of: &["run", "uses"],
+    ..MappingSchema::UNCONSTRAINED
+});
+
+/// Documentation: <https://docs.github.com/en/actions/writing-workflows/workflow-syntax-for-github-actions#jobs>
+const JOB: Schema = Schema::Mapping(&MappingSchema {
+    keys: &[
+        ("name", &STRING),
+        ("needs", &STRINGS),
+        ("if", &STRING),
+        ("permissions", &PERMISSIONS),
+        (
+            "runs-on",
+            &Schema::Either(&[
+                &STRINGS,
+                &Schema::Mapping(&MappingSchema {
+                    keys: &[("group", &STRING), ("labels", &STRINGS)],
+                    ..MappingSchema::UNCONSTRAINED
+                }),
+            ]),
+        ),
+        (
+            "environment",
+            &Schema::Either(&[
+                &STRING,
+                &Schema::Mapping(&MappingSchema {
+                    keys: &[("name", &STRING), ("url", &STRING)],
+                    required: &["name"],
+                    ..MappingSchema::UNCONSTRAINED
+                }),
+            ]),
+        ),
+        ("concurrency", &CONCURRENCY),
+        ("outputs", &STRING_MAP),
+        ("env", &STRING_MAP_OR_EXPRESSION),
+        ("defaults", &DEFAULTS),
+        ("steps", &Schema::Sequence(&STEP)),
+        ("timeout-minutes", &Schema::Number),
+        (
+            "strategy",
+            &Schema::Mapping(&MappingSchema {
+                keys: &[
+                    (
+                        "matrix",
+                        &Schema::Either(&[&Schema::Map(&Schema::Any), &STRING]),
+                    ),
+                    ("fail-fast", &Schema::Boolean),
+                    ("max-parallel", &Schema::Integer),
+                ],
+                ..MappingSchema::UNCONSTRAINED
+            }),
+        ),
+        ("continue-on-error", &Schema::Boolean),
+        ("container", &CONTAINER),
+        ("services", &Schema::Map(&SERVICE)),
+        ("uses", &STRING),
+        ("with", &STRING_MAP_OR_EXPRESSION),
+        (
+            "secrets",
+            &Schema::Either(&[&Schema::Enum(&["inherit"]), &STRING_MAP]),
+        ),
+    ],
+    one_of: &["runs-on", "uses"],
+    exclusive: &[("uses", "steps")],
+    ..MappingSchema::UNCONSTRAINED
+});
+
+/// Documentation: <https://docs.github.com/en/actions/writing-workflows/workflow-syntax-for-github-actions>
+pub const WORKFLOW: Schema = Schema::Mapping(&MappingSchema {
+    keys: &[
+        ("name", &STRING),
+        ("run-name", &STRING),
+        ("on", &ON),
+        ("permissions", &PERMISSIONS),
+        ("env", &STRING_MAP),
+        ("defaults", &DEFAULTS),
+        ("concurrency", &CONCURRENCY),
+        ("jobs", &Schema::Map(&JOB)),
+    ],
+    required: &["on", "jobs"],
+    ..MappingSchema::UNCONSTRAINED
+});
+
+impl Schema {
+    /// Returns `true` if the type of the node matches this schema (ignoring its contents).
+    fn matches_type(&self, node: &Node) -> bool {
+        match self {
+            Schema::Any => true,
+            Schema::Scalar
+            | Schema::Boolean
+            | Schema::Number
+            | Schema::Integer
+            | Schema::Enum(_) => matches!(node, Node::Scalar(_)),
+            Schema::Sequence(_) => matches!(node, Node::Sequence(_)),
+            Schema::Map(_) | Schema::Mapping(_) => matches!(node, Node::Mapping(_)),
+            Schema::Either(schemas) => schemas.iter().any(|schema| schema.matches_type(node)),
+        }
+    }
+
+    /// A description of the expected value.
+    fn expected(&self) -> String {
+        match self {
+            Schema::Any => "any value".to_owned(),
+            Schema::Scalar => "a string".to_owned(),
+            Schema::Boolean => bool::EXPECTED.to_owned(),
+            Schema::Number => f64::EXPECTED.to_owned(),
+            Schema::Integer => u32::EXPECTED.to_owned(),
+            Schema::Enum(values) => format!("one of {}", quoted_list(values, "or")),
+            Schema::Sequence(_) => "a sequence".to_owned(),
+            Schema::Map(_) | Schema::Mapping(_) => "a mapping".to_owned(),
+            Schema::Either(schemas) => schemas
+                .iter()
+                .map(|schema| schema.expected())
+                .collect::<Vec<_>>()
+                .join(" or "),
+        }
+    }
+
+    /// Validates the node (with the given path and location of its key) against this schema.
+    fn validate(
+        &self,
+        source: &str,
+        node: &Node,
+        path: &str,
+        key_span: Option<Span>,
+        errors: &mut Vec<ParseError>,
+    ) {
+        let error = |message: String| {
+            ParseError::new(
+                node_span(source, node, key_span).unwrap_or_else(Span::new_blank),
+                (!path.is_empty()).then(|| path.to_owned()),
+                message,
+            )
+        };
+        if !self.matches_type(node) {
+            let found = match node {
+                Node::Scalar(_) => "a string",
+                Node::Sequence(_) => "a sequence",
+                Node::Mapping(_) => "a mapping",
+            };
+            errors.push(error(format!(
+                "invalid type: expected {}, found {found}",
+                self.expected()
+            )));
+            return;
+        }
+
+        match (self, node) {
+            (Schema::Enum(values), Node::Scalar(scalar)) => {
+                let value = scalar.as_str();
+                if !values.contains(&value) {
+                    // Long lists of values (e.g. events) are not helpful in the message.
+                    let message = match suggestion(value, values.iter().copied()) {
+                        Some(suggestion) => {
+                            format!("invalid value `{value}`, did you mean `{suggestion}`")
+                        }
+                        None if values.len() > MAX_EXPECTED_VALUES => {
+                            format!("invalid value `{value}`")
+                        }
+                        None => format!("invalid value `{value}`, expected {}", self.expected()),
+                    };
+                    errors.push(error(message));
+                }
+            }
+            (Schema::Boolean | Schema::Number | Schema::Integer, Node::Scalar(scalar)) => {
+                let value = scalar.as_str();
+                let is_valid = match self {
+                    Schema::Boolean => ValueOrExpression::<bool>::parse(value).is_some(),
+                    Schema::Number => ValueOrExpression::<f64>::parse(value).is_some(),
+                    _ => ValueOrExpression::<u32>::parse(value).is_some(),
+                };
+                if !is_valid {
+                    errors.push(error(format!(
+                        "invalid value `{value}`, expected {}",
+                        self.expected()
+                    )));
+                }
+            }
+            (Schema::Sequence(schema), Node::Sequence(sequence)) => {
+                for (index, item) in sequence.iter().enumerate() {
+                    schema.validate(source, item, &format!("{path}[{index}]"), None, errors);
+                }
+            }
+            (Schema::Map(schema), Node::Mapping(mapping)) => {
+                for (key, value) in mapping.iter() {
+                    schema.validate(
+                        source,
+                        value,
+                        &join_path(path, key.as_str()),
+                        Some(*key.span()),
+                        errors,
+                    );
+                }
+            }
+            (Schema::Mapping(mapping_schema), Node::Mapping(mapping)) => {
+                let has_key = |name: &str| mapping.keys().any(|key| key.as_str() == name);
+                for name in mapping_schema.required {
+                    if !has_key(name) {
+                        errors.push(error(format!("missing key `{name}`")));
+                    }
+                }
+                if !mapping_schema.one_of.is_empty()
+                    && !mapping_schema.one_of.iter().any(|name| has_key(name))
+                {
+                    errors.push(error(format!(
+                        "missing key {}",
+                        quoted_list(mapping_schema.one_of, "or")
+                    )));
+                }
+                let mut exclusive: Vec<_> = mapping_schema
+                    .one_of
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(index, first)| {
+                        mapping_schema.one_of[index + 1..]
+                            .iter()
+                            .map(move |second| (*first, *second))
+                    })
+                    .collect();
+                exclusive.extend(mapping_schema.exclusive);
+                for (first, second) in exclusive {
+                    if has_key(first) && has_key(second) {
+                        errors.push(error(format!(
+                            "keys `{first}` and `{second}` cannot be used together"
+                        )));
+                    }
+                }
+
+                for (key, value) in mapping.iter() {
+                    let key_span = key
+                        .span()
+                        .start()
+                        .and_then(|start| scalar_span(source, start, key.as_str()))
+                        .or_else(|| Some(*key.span()));
+                    match mapping_schema
+                        .keys
+                        .iter()
+                        .find(|(name, _schema)| *name == key.as_str())
+                    {
+                        Some((_name, schema)) => schema.validate(
+                            source,
+                            value,
+                            &join_path(path, key.as_str()),
+                            key_span,
+                            errors,
+                        ),
+                        None => {
+                            let names = mapping_schema.keys.iter().map(|(name, _schema)| *name);
+                            let message = match suggestion(key.as_str(), names) {
+                                Some(suggestion) => format!(
+                                    "unknown key `{}`, did you mean `{suggestion}`",
+                                    key.as_str()
+                                ),
+                                None => format!("unknown key `{}`", key.as_str()),
+                            };
+                            errors.push(ParseError::new(
+                                key_span.unwrap_or_else(Span::new_blank),
+                                (!path.is_empty()).then(|| path.to_owned()),
+                                message,
+                            ));
+                        }
+                    }
+                }
+            }
+            (Schema::Either(schemas), _) => {
+                if let Some(schema) = schemas.iter().find(|schema| schema.matches_type(node)) {
+                    schema.validate(source, node, path, key_span, errors);
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Validates the YAML document of a workflow against the workflow syntax.
+///
+/// Returns an error for each unknown key, value of the wrong type and missing or conflicting key.
+pub fn validate_workflow(source: &str, node: &Node) -> Vec<ParseError> {
+    let mut errors = Vec::new();
+    WORKFLOW.validate(source, node, "", None, &mut errors);
+    errors
+}
+
+/// Parses the YAML source of a workflow and validates it against the workflow syntax.
+pub fn validate_workflow_source(source: &str) -> Vec<ParseError> {
+    match marked_yaml::parse_yaml(0, source) {
+        Ok(node) => validate_workflow(source, &node),
+        Err(err) => vec![ParseError::from_load_error(&err)],
+    }
+}
+
+/// Appends a key to the path of its mapping.
+fn join_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_owned()
+    } else {
+        format!("{path}.{key}")
+    }
+}
+
+/// Formats a list of names like "`a`, `b` or `c`".
+fn quoted_list(names: &[&str], conjunction: &str) -> String {
+    let quoted: Vec<_> = names.iter().map(|name| format!("`{name}`")).collect();
+    match quoted.split_last() {
+        Some((last, rest)) if !rest.is_empty() => {
+            format!("{} {conjunction} {last}", rest.join(", "))
+        }
+        _ => quoted.concat(),
+    }
+}
+
+/// Returns the known name that is most similar to the unknown name, if it is likely a typo.
+fn suggestion<'a>(name: &str, names: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    let normalized = name.to_lowercase().replace('_', "-");
+    names
+        .map(|name| (name, edit_distance(&normalized, &name.replace('_', "-"))))
+        .filter(|(name, distance)| *distance <= 2.min(name.len() / 3))
+        .min_by_key(|(_name, distance)| *distance)
+        .map(|(name, _distance)| name)
+}
+
+/// The Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn errors(source: &str) -> Vec<String> {
+        validate_workflow_source(source)
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_validate_workflow() {
+        let source = r#"
+on:
+  push:
+    branches: [main]
+  workflow_dispatch:
+permissions: {}
+jobs:
+  build:
+    runs-on: ubuntu-latest
+    permissions:
+      contents: read
+      pull-requests: write
+    strategy:
+      matrix:
+        os: [ubuntu-latest]
+    steps:
+      - uses: actions/checkout@v4
+        with:
+          persist-credentials: false
+      - run: make
+  call:
+    uses: ./.github/workflows/reusable.yml
+    secrets: inherit
+"#;
+        assert_eq!(errors(source), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_validate_workflow_errors() {
+        let source = r#"on: [push, pull_reqest]
+permisions: {}
+jobs:
+  build:
+    runs_on: ubuntu-latest
+    permissions:
+      content: read
+      issues: wrte
+    steps:
+      - run: make
+        uses: actions/checkout@v4
+      - name: foo
+      - run: [make]
+    services:
+      redis: redis:7
+"#;
+        assert_eq!(
+            errors(source),
+            vec![
+                "1:12: on[1]: invalid value `pull_reqest`, did you mean `pull_request`",
+                "2:1: unknown key `permisions`, did you mean `permissions`",
+                "4:3: jobs.build: missing key `runs-on` or `uses`",
+                "5:5: jobs.build: unknown key `runs_on`, did you mean `runs-on`",
+                "7:7: jobs.build.permissions: unknown key `content`, did you mean `contents`",
+                "8:15: jobs.build.permissions.issues: invalid value `wrte`, did you mean `write`",
+                "10:9: jobs.build.steps[0]: keys `run` and `uses` cannot be used together",
+                "12:9: jobs.build.steps[1]: missing key `run` or `uses`",
+                "13:9: jobs.build.steps[2].run: invalid type: expected a string, found a sequence",
+                "15:14: jobs.build.services.redis: invalid type: expected a mapping, found a string",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_workflow_value_types() {
+        let source = r#"on: [push]
+concurrency:
+  group: ci
+  cancel-in-progress: ${{ github.ref != 'refs/heads/main' }}
+jobs:
+  build:
+    runs-on: ubuntu-latest
+    timeout-minutes: abc
+    continue-on-error: maybe
+    strategy:
+      fail-fast: nope
+      max-parallel: 1.5
+    steps:
+      - run: make
+        timeout-minutes: xyz
+        continue-on-error: ${{ matrix.experimental }}
+      - run: make test
+        timeout-minutes: 2.5
+"#;
+        assert_eq!(
+            errors(source),
+            vec![
+                "8:22: jobs.build.timeout-minutes: invalid value `abc`, expected a number or an expression",
+                "9:24: jobs.build.continue-on-error: invalid value `maybe`, expected a boolean or an expression",
+                "11:18: jobs.build.strategy.fail-fast: invalid value `nope`, expected a boolean or an expression",
+                "12:21: jobs.build.strategy.max-parallel: invalid value `1.5`, expected a non-negative integer or an expression",
+                "15:26: jobs.build.steps[0].timeout-minutes: invalid value `xyz`, expected a number or an expression",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_suggestion() {
+        let names = ["runs-on", "permissions"];
+        assert_eq!(suggestion("runs_on", names.into_iter()), Some("runs-on"));
+        assert_eq!(
+            suggestion("permisions", names.into_iter()),
+            Some("permissions")
+        );
+        assert_eq!(suggestion("foo", names.into_iter()), None);
+    }
+}
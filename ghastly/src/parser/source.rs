@@ -0,0 +1,108 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+use super::error::ParseError;
+use super::location::{indentation, locate, offset, scalar_location, scalar_span};
+use marked_yaml::types::MarkedMappingNode;
+use marked_yaml::{Marker, Node, Span, Spanned};
+use serde::de::DeserializeOwned;
+use std::io::Read;
+use std::ops::Range;
+
+/// The YAML source of a file and its YAML document.
+///
+/// The YAML loader only records where values start, so the source is needed to find out where
+/// they end (e.g. to replace them) and the document to find out where mapping keys are.
+#[derive(Debug)]
+pub struct Source {
+    text: String,
+    node: Node,
+}
+
+impl Default for Source {
+    /// An empty source, used as placeholder until a file has been deserialized from its source.
+    fn default() -> Self {
+        Self {
+            text: String::new(),
+            node: Node::Mapping(MarkedMappingNode::new_empty(Span::new_blank())),
+        }
+    }
+}
+
+impl Source {
+    /// Reads the YAML source from the given reader and parses it.
+    pub fn from_reader<R>(reader: &mut R) -> crate::Result<Source>
+    where
+        R: Read,
+    {
+        let mut text = String::new();
+        reader.read_to_string(&mut text)?;
+        let node =
+            marked_yaml::parse_yaml(0, &text).map_err(|err| ParseError::from_load_error(&err))?;
+        Ok(Self { text, node })
+    }
+
+    /// Deserializes the YAML document.
+    pub fn deserialize<T>(&self) -> crate::Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        Ok(marked_yaml::from_node(&self.node)
+            .map_err(|err| ParseError::from_node_error(&self.text, &self.node, err))?)
+    }
+
+    /// The YAML source text.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// The YAML document.
+    ///
+    /// Unlike deserialized values, it also contains the locations of mapping keys.
+    #[inline]
+    pub fn node(&self) -> &Node {
+        &self.node
+    }
+
+    /// Maps a byte range inside the value of a scalar to its location in the YAML source.
+    ///
+    /// See [`locate`] for details.
+    pub fn locate(&self, scalar: &Spanned<String>, range: Range<usize>) -> Span {
+        locate(&self.text, scalar, range)
+    }
+
+    /// The source text at the given location (if the span has a start and an end).
+    pub fn text(&self, span: &Span) -> Option<&str> {
+        let start = offset(&self.text, span.start()?)?;
+        let end = offset(&self.text, span.end()?)?;
+        self.text.get(start..end)
+    }
+
+    /// The location of a single-line scalar that starts at the given location.
+    ///
+    /// See [`scalar_span`] for details.
+    pub fn scalar_span(&self, start: &Marker, value: &str) -> Option<Span> {
+        scalar_span(&self.text, start, value)
+    }
+
+    /// The location of a scalar with the given value, including its end if it is a single-line
+    /// scalar.
+    ///
+    /// See [`scalar_location`] for details.
+    pub fn scalar_location<T>(&self, scalar: &Spanned<T>, value: &str) -> Span {
+        scalar_location(&self.text, scalar, value)
+    }
+
+    /// The indentation of the block mapping key at the given location.
+    ///
+    /// See [`indentation`] for details.
+    pub fn indentation(&self, marker: &Marker) -> Option<usize> {
+        indentation(&self.text, marker)
+    }
+}
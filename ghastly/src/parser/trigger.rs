@@ -0,0 +1,599 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+use super::value::StringOrList;
+use super::workflow::Map;
+use marked_yaml::{Span, Spanned};
+use serde::de::value::{MapAccessDeserializer, SeqAccessDeserializer};
+use serde::de::{Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::Deserialize;
+use std::fmt;
+
+/// A list of spanned strings, e.g. the options of a `choice` input.
+pub type StringList = Vec<Spanned<String>>;
+
+/// Defines which events can cause the workflow to run.
+///
+/// The `on` field can either be a single event name, a list of event names or a map of event
+/// names to their configuration. Use [`WorkflowTrigger::iter`] to iterate over the triggers
+/// regardless of the form that was used.
+///
+/// Documentation: <https://docs.github.com/en/actions/writing-workflows/workflow-syntax-for-github-actions#on>
+#[derive(Deserialize, Debug)]
+#[serde(transparent)]
+pub struct WorkflowTrigger(Spanned<TriggerSyntax>);
+
+impl WorkflowTrigger {
+    /// Iterates over all triggers.
+    pub fn iter(&self) -> impl Iterator<Item = Trigger<'_>> {
+        let span = self.0.span();
+        let triggers: Vec<_> = match &*self.0 {
+            TriggerSyntax::Event(event) => vec![Trigger {
+                event,
+                span,
+                config: None,
+            }],
+            TriggerSyntax::List(events) => events
+                .iter()
+                .map(|event| Trigger {
+                    event,
+                    span: event.span(),
+                    config: None,
+                })
+                .collect(),
+            TriggerSyntax::Map(entries) => entries
+                .iter()
+                .map(|(event, config)| Trigger {
+                    event,
+                    span: event.span(),
+                    config: Some(config),
+                })
+                .collect(),
+        };
+        triggers.into_iter()
+    }
+
+    /// Returns the trigger for the given event (if any).
+    pub fn get(&self, event: &Event) -> Option<Trigger<'_>> {
+        self.iter().find(|trigger| trigger.event() == event)
+    }
+
+    /// Returns `true` if the workflow is triggered by the given event.
+    pub fn is_triggered_by(&self, event: &Event) -> bool {
+        self.get(event).is_some()
+    }
+}
+
+/// A single event that triggers the workflow, with its optional configuration.
+#[derive(Debug, Clone, Copy)]
+pub struct Trigger<'a> {
+    event: &'a Event,
+    span: &'a Span,
+    config: Option<&'a Spanned<EventConfig>>,
+}
+
+impl<'a> Trigger<'a> {
+    /// The event that triggers the workflow.
+    pub fn event(&self) -> &'a Event {
+        self.event
+    }
+
+    /// The location of the event name.
+    pub fn span(&self) -> &'a Span {
+        self.span
+    }
+
+    /// The configuration of the event, if the map form was used.
+    pub fn config(&self) -> Option<&'a Spanned<EventConfig>> {
+        self.config
+    }
+
+    /// Returns the event filter of this trigger (if any).
+    pub fn filter(&self) -> Option<&'a EventFilter> {
+        self.config.and_then(|config| match &**config {
+            EventConfig::Filter(filter) => Some(filter.as_ref()),
+            EventConfig::Schedule(_) => None,
+        })
+    }
+
+    /// Returns the cron schedules of this trigger (if any).
+    pub fn schedule(&self) -> Option<&'a [Spanned<Schedule>]> {
+        self.config.and_then(|config| match &**config {
+            EventConfig::Filter(_) => None,
+            EventConfig::Schedule(schedule) => Some(schedule.as_slice()),
+        })
+    }
+}
+
+/// An event that can trigger a workflow.
+///
+/// Documentation: <https://docs.github.com/en/actions/writing-workflows/choosing-when-your-workflow-runs/events-that-trigger-workflows>
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(from = "String")]
+pub enum Event {
+    BranchProtectionRule,
+    CheckRun,
+    CheckSuite,
+    Create,
+    Delete,
+    Deployment,
+    DeploymentStatus,
+    Discussion,
+    DiscussionComment,
+    Fork,
+    Gollum,
+    IssueComment,
+    Issues,
+    Label,
+    MergeGroup,
+    Milestone,
+    PageBuild,
+    Project,
+    ProjectCard,
+    ProjectColumn,
+    Public,
+    PullRequest,
+    PullRequestReview,
+    PullRequestReviewComment,
+    PullRequestTarget,
+    Push,
+    RegistryPackage,
+    Release,
+    RepositoryDispatch,
+    Schedule,
+    Status,
+    Watch,
+    WorkflowCall,
+    WorkflowDispatch,
+    WorkflowRun,
+    /// An event that is not known to ghastly.
+    Other(String),
+}
+
+impl Event {
+    /// The event name as used in the workflow file.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Event::BranchProtectionRule => "branch_protection_rule",
+            Event::CheckRun => "check_run",
+            Event::CheckSuite => "check_suite",
+            Event::Create => "create",
+            Event::Delete => "delete",
+            Event::Deployment => "deployment",
+            Event::DeploymentStatus => "deployment_status",
+            Event::Discussion => "discussion",
+            Event::DiscussionComment => "discussion_comment",
+            Event::Fork => "fork",
+            Event::Gollum => "gollum",
+            Event::IssueComment => "issue_comment",
+            Event::Issues => "issues",
+            Event::Label => "label",
+            Event::MergeGroup => "merge_group",
+            Event::Milestone => "milestone",
+            Event::PageBuild => "page_build",
+            Event::Project => "project",
+            Event::ProjectCard => "project_card",
+            Event::ProjectColumn => "project_column",
+            Event::Public => "public",
+            Event::PullRequest => "pull_request",
+            Event::PullRequestReview => "pull_request_review",
+            Event::PullRequestReviewComment => "pull_request_review_comment",
+            Event::PullRequestTarget => "pull_request_target",
+            Event::Push => "push",
+            Event::RegistryPackage => "registry_package",
+            Event::Release => "release",
+            Event::RepositoryDispatch => "repository_dispatch",
+            Event::Schedule => "schedule",
+            Event::Status => "status",
+            Event::Watch => "watch",
+            Event::WorkflowCall => "workflow_call",
+            Event::WorkflowDispatch => "workflow_dispatch",
+            Event::WorkflowRun => "workflow_run",
+            Event::Other(name) => name,
+        }
+    }
+}
+
+impl From<String> for Event {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "branch_protection_rule" => Event::BranchProtectionRule,
+            "check_run" => Event::CheckRun,
+            "check_suite" => Event::CheckSuite,
+            "create" => Event::Create,
+            "delete" => Event::Delete,
+            "deployment" => Event::Deployment,
+            "deployment_status" => Event::DeploymentStatus,
+            "discussion" => Event::Discussion,
+            "discussion_comment" => Event::DiscussionComment,
+            "fork" => Event::Fork,
+            "gollum" => Event::Gollum,
+            "issue_comment" => Event::IssueComment,
+            "issues" => Event::Issues,
+            "label" => Event::Label,
+            "merge_group" => Event::MergeGroup,
+            "milestone" => Event::Milestone,
+            "page_build" => Event::PageBuild,
+            "project" => Event::Project,
+            "project_card" => Event::ProjectCard,
+            "project_column" => Event::ProjectColumn,
+            "public" => Event::Public,
+            "pull_request" => Event::PullRequest,
+            "pull_request_review" => Event::PullRequestReview,
+            "pull_request_review_comment" => Event::PullRequestReviewComment,
+            "pull_request_target" => Event::PullRequestTarget,
+            "push" => Event::Push,
+            "registry_package" => Event::RegistryPackage,
+            "release" => Event::Release,
+            "repository_dispatch" => Event::RepositoryDispatch,
+            "schedule" => Event::Schedule,
+            "status" => Event::Status,
+            "watch" => Event::Watch,
+            "workflow_call" => Event::WorkflowCall,
+            "workflow_dispatch" => Event::WorkflowDispatch,
+            "workflow_run" => Event::WorkflowRun,
+            _ => Event::Other(value),
+        }
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The configuration of an event in the map form of the `on` field.
+#[derive(Debug)]
+pub enum EventConfig {
+    /// Filters and settings for most events.
+    Filter(Box<EventFilter>),
+    /// Cron schedules for the `schedule` event.
+    Schedule(Vec<Spanned<Schedule>>),
+}
+
+impl<'de> Deserialize<'de> for EventConfig {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct EventConfigVisitor;
+
+        impl<'de> Visitor<'de> for EventConfigVisitor {
+            type Value = EventConfig;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("an event filter map or a list of schedules")
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                // Events without configuration are written as `push:` or `push: null`.
+                match value {
+                    "" | "~" | "null" => Ok(EventConfig::Filter(Box::default())),
+                    other => Err(E::invalid_value(serde::de::Unexpected::Str(other), &self)),
+                }
+            }
+
+            fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                Vec::deserialize(SeqAccessDeserializer::new(seq)).map(EventConfig::Schedule)
+            }
+
+            fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                EventFilter::deserialize(MapAccessDeserializer::new(map))
+                    .map(|filter| EventConfig::Filter(Box::new(filter)))
+            }
+        }
+
+        deserializer.deserialize_any(EventConfigVisitor)
+    }
+}
+
+/// Filters and settings of an event.
+///
+/// Which of these fields are meaningful depends on the event, e.g. `workflows` is only used by
+/// `workflow_run` and `inputs` only by `workflow_dispatch` and `workflow_call`.
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "kebab-case")]
+pub struct EventFilter {
+    /// Activity types that trigger the workflow.
+    ///
+    /// Documentation: <https://docs.github.com/en/actions/writing-workflows/workflow-syntax-for-github-actions#onevent_nametypes>
+    pub types: Option<Spanned<StringOrList>>,
+    /// Branches that trigger the workflow.
+    ///
+    /// Documentation: <https://docs.github.com/en/actions/writing-workflows/workflow-syntax-for-github-actions#onpull_requestpull_request_targetbranchesbranches-ignore>
+    pub branches: Option<Spanned<StringOrList>>,
+    /// Branches that do not trigger the workflow.
+    pub branches_ignore: Option<Spanned<StringOrList>>,
+    /// Tags that trigger the workflow.
+    ///
+    /// Documentation: <https://docs.github.com/en/actions/writing-workflows/workflow-syntax-for-github-actions#onpushbranchestagsbranches-ignoretags-ignore>
+    pub tags: Option<Spanned<StringOrList>>,
+    /// Tags that do not trigger the workflow.
+    pub tags_ignore: Option<Spanned<StringOrList>>,
+    /// File paths that trigger the workflow.
+    ///
+    /// Documentation: <https://docs.github.com/en/actions/writing-workflows/workflow-syntax-for-github-actions#onpushpull_requestpull_request_targetpathspaths-ignore>
+    pub paths: Option<Spanned<StringOrList>>,
+    /// File paths that do not trigger the workflow.
+    pub paths_ignore: Option<Spanned<StringOrList>>,
+    /// Workflows that trigger a `workflow_run` event.
+    ///
+    /// Documentation: <https://docs.github.com/en/actions/writing-workflows/workflow-syntax-for-github-actions#onworkflow_runbranchesbranches-ignore>
+    pub workflows: Option<Spanned<StringOrList>>,
+    /// Inputs of a `workflow_dispatch` or `workflow_call` event.
+    ///
+    /// Documentation: <https://docs.github.com/en/actions/writing-workflows/workflow-syntax-for-github-actions#onworkflow_dispatchinputs>
+    pub inputs: Option<Spanned<Map<WorkflowInput>>>,
+    /// Outputs of a `workflow_call` event.
+    ///
+    /// Documentation: <https://docs.github.com/en/actions/writing-workflows/workflow-syntax-for-github-actions#onworkflow_calloutputs>
+    pub outputs: Option<Spanned<Map<WorkflowCallOutput>>>,
+    /// Secrets of a `workflow_call` event.
+    ///
+    /// Documentation: <https://docs.github.com/en/actions/writing-workflows/workflow-syntax-for-github-actions#onworkflow_callsecrets>
+    pub secrets: Option<Spanned<Map<WorkflowCallSecret>>>,
+}
+
+/// An input of a `workflow_dispatch` or `workflow_call` event.
+#[derive(Deserialize, Debug)]
+pub struct WorkflowInput {
+    /// A description of the input.
+    pub description: Option<Spanned<String>>,
+    /// Whether the input is required.
+    pub required: Option<Spanned<bool>>,
+    /// The default value of the input.
+    pub default: Option<Spanned<String>>,
+    /// The type of the input.
+    #[serde(rename = "type")]
+    pub input_type: Option<Spanned<InputType>>,
+    /// The options of a `choice` input.
+    pub options: Option<Spanned<StringList>>,
+}
+
+/// The type of a workflow input.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum InputType {
+    Boolean,
+    Choice,
+    Environment,
+    Number,
+    String,
+}
+
+/// An output of a `workflow_call` event.
+#[derive(Deserialize, Debug)]
+pub struct WorkflowCallOutput {
+    /// A description of the output.
+    pub description: Option<Spanned<String>>,
+    /// The value of the output (usually an expression).
+    pub value: Spanned<String>,
+}
+
+/// A secret of a `workflow_call` event.
+#[derive(Deserialize, Debug)]
+pub struct WorkflowCallSecret {
+    /// A description of the secret.
+    pub description: Option<Spanned<String>>,
+    /// Whether the secret is required.
+    pub required: Option<Spanned<bool>>,
+}
+
+/// A cron schedule of the `schedule` event.
+///
+/// Documentation: <https://docs.github.com/en/actions/writing-workflows/workflow-syntax-for-github-actions#onschedule>
+#[derive(Deserialize, Debug)]
+pub struct Schedule {
+    /// The cron expression.
+    pub cron: Spanned<String>,
+}
+
+/// The syntactic forms of the `on` field.
+#[derive(Debug)]
+enum TriggerSyntax {
+    /// A single event, e.g. `on: push`.
+    Event(Event),
+    /// A list of events, e.g. `on: [push, pull_request]`.
+    List(Vec<Spanned<Event>>),
+    /// A map of events to their configuration.
+    Map(Vec<(Spanned<Event>, Spanned<EventConfig>)>),
+}
+
+impl<'de> Deserialize<'de> for TriggerSyntax {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct TriggerSyntaxVisitor;
+
+        impl<'de> Visitor<'de> for TriggerSyntaxVisitor {
+            type Value = TriggerSyntax;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("an event name, a list of event names or a map of events")
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(TriggerSyntax::Event(Event::from(value.to_owned())))
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                let mut events = Vec::new();
+                while let Some(event) = seq.next_element()? {
+                    events.push(event);
+                }
+                Ok(TriggerSyntax::List(events))
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let mut entries = Vec::new();
+                while let Some(entry) = map.next_entry()? {
+                    entries.push(entry);
+                }
+                Ok(TriggerSyntax::Map(entries))
+            }
+        }
+
+        deserializer.deserialize_any(TriggerSyntaxVisitor)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct OnlyTrigger {
+        on: Spanned<WorkflowTrigger>,
+    }
+
+    fn parse(yaml: &str) -> Spanned<WorkflowTrigger> {
+        marked_yaml::from_yaml::<OnlyTrigger>(0, yaml).unwrap().on
+    }
+
+    fn events(trigger: &WorkflowTrigger) -> Vec<(&str, usize, usize)> {
+        trigger
+            .iter()
+            .map(|trigger| {
+                let start = trigger.span().start().unwrap();
+                (trigger.event().as_str(), start.line(), start.column())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_single_event() {
+        let trigger = parse("on: push\n");
+        assert_eq!(events(&trigger), vec![("push", 1, 5)]);
+        assert!(trigger.is_triggered_by(&Event::Push));
+        assert!(trigger.get(&Event::Push).unwrap().config().is_none());
+    }
+
+    #[test]
+    fn test_event_list() {
+        let trigger = parse("on: [push, pull_request_target, some_new_event]\n");
+        assert_eq!(
+            events(&trigger),
+            vec![
+                ("push", 1, 6),
+                ("pull_request_target", 1, 12),
+                ("some_new_event", 1, 33)
+            ]
+        );
+        assert!(trigger.is_triggered_by(&Event::PullRequestTarget));
+        assert!(trigger.is_triggered_by(&Event::Other("some_new_event".to_string())));
+        assert!(!trigger.is_triggered_by(&Event::PullRequest));
+    }
+
+    #[test]
+    fn test_event_map() {
+        let trigger = parse(
+            r#"
+on:
+  push:
+    branches: [main]
+    tags-ignore:
+      - "v*"
+  pull_request:
+  schedule:
+    - cron: "0 0 * * *"
+  workflow_dispatch:
+    inputs:
+      name:
+        type: choice
+        required: true
+        options: [a, b]
+"#,
+        );
+        assert_eq!(
+            events(&trigger),
+            vec![
+                ("push", 3, 3),
+                ("pull_request", 7, 3),
+                ("schedule", 8, 3),
+                ("workflow_dispatch", 10, 3)
+            ]
+        );
+
+        let push = trigger.get(&Event::Push).unwrap().filter().unwrap();
+        assert!(push.branches.as_ref().unwrap().contains("main"));
+        assert!(push.tags_ignore.as_ref().unwrap().contains("v*"));
+        assert!(push.paths.is_none());
+
+        let pull_request = trigger.get(&Event::PullRequest).unwrap().filter().unwrap();
+        assert!(pull_request.branches.is_none());
+
+        let schedule = trigger.get(&Event::Schedule).unwrap().schedule().unwrap();
+        assert_eq!(schedule[0].cron, "0 0 * * *");
+        assert_eq!(schedule[0].cron.span().start().unwrap().line(), 9);
+
+        let dispatch = trigger
+            .get(&Event::WorkflowDispatch)
+            .unwrap()
+            .filter()
+            .unwrap();
+        let input = dispatch.inputs.as_ref().unwrap().get("name").unwrap();
+        assert_eq!(*input.input_type.as_ref().unwrap(), InputType::Choice);
+        assert!(**input.required.as_ref().unwrap());
+        assert_eq!(input.options.as_ref().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_single_string_filters() {
+        let trigger = parse(
+            r#"
+on:
+  pull_request_target:
+    types: opened
+    branches: main
+  workflow_run:
+    workflows: CI
+"#,
+        );
+        let pull_request_target = trigger
+            .get(&Event::PullRequestTarget)
+            .unwrap()
+            .filter()
+            .unwrap();
+        let types = pull_request_target.types.as_ref().unwrap();
+        assert_eq!(
+            types
+                .iter(types.span())
+                .map(|(value, span)| (value, span.start().unwrap().line()))
+                .collect::<Vec<_>>(),
+            vec![("opened", 4)]
+        );
+        assert!(pull_request_target
+            .branches
+            .as_ref()
+            .unwrap()
+            .contains("main"));
+
+        let workflow_run = trigger.get(&Event::WorkflowRun).unwrap().filter().unwrap();
+        assert!(workflow_run.workflows.as_ref().unwrap().contains("CI"));
+    }
+}
@@ -0,0 +1,326 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+use super::workflow::Map;
+use marked_yaml::{Span, Spanned};
+use serde::de::value::{MapAccessDeserializer, SeqAccessDeserializer};
+use serde::de::{Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::Deserialize;
+use std::fmt;
+use std::marker::PhantomData;
+
+/// A value that is either a string or a map, e.g. `environment: production` or
+/// `environment: { name: production, url: ... }`.
+///
+/// Unlike `serde_either::StringOrStruct`, this does not buffer the input before deserializing the
+/// map, so that spans of nested `Spanned` values are preserved.
+#[derive(Debug)]
+pub enum StringOrMap<T> {
+    /// The short form.
+    String(String),
+    /// The long form.
+    Map(T),
+}
+
+impl<T> StringOrMap<T> {
+    /// Returns the long form (if used).
+    pub fn as_map(&self) -> Option<&T> {
+        match self {
+            StringOrMap::String(_) => None,
+            StringOrMap::Map(value) => Some(value),
+        }
+    }
+}
+
+impl<'de, T> Deserialize<'de> for StringOrMap<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct StringOrMapVisitor<T>(PhantomData<T>);
+
+        impl<'de, T> Visitor<'de> for StringOrMapVisitor<T>
+        where
+            T: Deserialize<'de>,
+        {
+            type Value = StringOrMap<T>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a string or a map")
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(StringOrMap::String(value.to_owned()))
+            }
+
+            fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                T::deserialize(MapAccessDeserializer::new(map)).map(StringOrMap::Map)
+            }
+        }
+
+        deserializer.deserialize_any(StringOrMapVisitor(PhantomData))
+    }
+}
+
+/// A value that is either a single string or a list of strings, e.g. `needs: build` or
+/// `needs: [build, test]`.
+#[derive(Debug)]
+pub enum StringOrList {
+    /// A single string.
+    String(String),
+    /// A list of strings.
+    List(Vec<Spanned<String>>),
+}
+
+impl StringOrList {
+    /// Iterates over all strings and their locations.
+    ///
+    /// Since the single string form does not carry its own span, the span of the whole value has
+    /// to be passed in.
+    pub fn iter<'a>(&'a self, span: &'a Span) -> impl Iterator<Item = (&'a str, &'a Span)> {
+        let items: Vec<_> = match self {
+            StringOrList::String(value) => vec![(value.as_str(), span)],
+            StringOrList::List(values) => values
+                .iter()
+                .map(|value| (value.as_str(), value.span()))
+                .collect(),
+        };
+        items.into_iter()
+    }
+
+    /// Returns `true` if the value contains the given string.
+    pub fn contains(&self, value: &str) -> bool {
+        match self {
+            StringOrList::String(item) => item == value,
+            StringOrList::List(items) => items.iter().any(|item| item.as_str() == value),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for StringOrList {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct StringOrListVisitor;
+
+        impl<'de> Visitor<'de> for StringOrListVisitor {
+            type Value = StringOrList;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a string or a list of strings")
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(StringOrList::String(value.to_owned()))
+            }
+
+            fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                Vec::deserialize(SeqAccessDeserializer::new(seq)).map(StringOrList::List)
+            }
+        }
+
+        deserializer.deserialize_any(StringOrListVisitor)
+    }
+}
+
+/// A scalar that can be parsed from a YAML scalar.
+pub trait Scalar: Sized {
+    /// A description of the expected value, for error messages.
+    const EXPECTED: &'static str;
+
+    /// Parses the scalar, or returns `None` if it is not a valid value.
+    fn parse(value: &str) -> Option<Self>;
+}
+
+impl Scalar for bool {
+    const EXPECTED: &'static str = "a boolean or an expression";
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "true" | "True" | "TRUE" => Some(true),
+            "false" | "False" | "FALSE" => Some(false),
+            _ => None,
+        }
+    }
+}
+
+impl Scalar for f64 {
+    const EXPECTED: &'static str = "a number or an expression";
+
+    fn parse(value: &str) -> Option<Self> {
+        value.parse().ok().filter(|value: &f64| value.is_finite())
+    }
+}
+
+impl Scalar for u32 {
+    const EXPECTED: &'static str = "a non-negative integer or an expression";
+
+    fn parse(value: &str) -> Option<Self> {
+        value.parse().ok()
+    }
+}
+
+/// A value that can also be set by an expression, e.g. `timeout-minutes: 30` or
+/// `timeout-minutes: ${{ inputs.timeout }}`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValueOrExpression<T> {
+    /// A literal value.
+    Value(T),
+    /// An expression (including the surrounding `${{` and `}}`) that is evaluated at runtime.
+    Expression(String),
+}
+
+impl<T: Scalar> ValueOrExpression<T> {
+    /// Parses a YAML scalar, or returns `None` if it is neither a valid value nor an expression.
+    pub fn parse(value: &str) -> Option<Self> {
+        let trimmed = value.trim();
+        if trimmed.starts_with("${{") && trimmed.ends_with("}}") {
+            return Some(ValueOrExpression::Expression(value.to_owned()));
+        }
+        T::parse(trimmed).map(ValueOrExpression::Value)
+    }
+}
+
+impl<T: Copy> ValueOrExpression<T> {
+    /// Returns the literal value, or `None` if the value is set by an expression.
+    pub fn value(&self) -> Option<T> {
+        match self {
+            ValueOrExpression::Value(value) => Some(*value),
+            ValueOrExpression::Expression(_) => None,
+        }
+    }
+}
+
+impl<'de, T> Deserialize<'de> for ValueOrExpression<T>
+where
+    T: Scalar,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct ValueOrExpressionVisitor<T>(PhantomData<T>);
+
+        impl<'de, T> Visitor<'de> for ValueOrExpressionVisitor<T>
+        where
+            T: Scalar,
+        {
+            type Value = ValueOrExpression<T>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str(T::EXPECTED)
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                ValueOrExpression::parse(value)
+                    .ok_or_else(|| E::invalid_value(serde::de::Unexpected::Str(value), &self))
+            }
+        }
+
+        deserializer.deserialize_any(ValueOrExpressionVisitor(PhantomData))
+    }
+}
+
+/// An arbitrary YAML value, e.g. the value of a matrix dimension.
+///
+/// All scalars are represented as strings, since YAML does not distinguish between them before
+/// they are interpreted.
+#[derive(Debug)]
+pub enum Value {
+    /// A scalar.
+    String(String),
+    /// A sequence.
+    Sequence(Vec<Spanned<Value>>),
+    /// A mapping.
+    Mapping(Map<Value>),
+}
+
+impl Value {
+    /// Returns the value as string (if it is a scalar).
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as sequence (if it is one).
+    pub fn as_sequence(&self) -> Option<&[Spanned<Value>]> {
+        match self {
+            Value::Sequence(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as mapping (if it is one).
+    pub fn as_mapping(&self) -> Option<&Map<Value>> {
+        match self {
+            Value::Mapping(values) => Some(values),
+            _ => None,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct ValueVisitor;
+
+        impl<'de> Visitor<'de> for ValueVisitor {
+            type Value = Value;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("any YAML value")
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(Value::String(value.to_owned()))
+            }
+
+            fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                Vec::deserialize(SeqAccessDeserializer::new(seq)).map(Value::Sequence)
+            }
+
+            fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                Map::deserialize(MapAccessDeserializer::new(map)).map(Value::Mapping)
+            }
+        }
+
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
@@ -6,11 +6,18 @@
 //
 // SPDX-License-Identifier: MPL-2.0
 
+use super::action_ref::ActionRef;
+use super::source::Source;
+use super::trigger::{Event, EventFilter, WorkflowTrigger};
+use super::value::{StringOrList, StringOrMap, Value, ValueOrExpression};
 use marked_yaml::Spanned;
+use serde::de::value::{MapAccessDeserializer, SeqAccessDeserializer};
+use serde::de::{Deserializer, MapAccess, SeqAccess, Visitor};
 use serde::Deserialize;
 use serde_either::StringOrStruct;
 use std::collections::BTreeMap;
 use std::convert::TryFrom;
+use std::fmt;
 use std::io::Read;
 use std::str::FromStr;
 
@@ -18,7 +25,6 @@ pub type Map<T> = BTreeMap<String, Spanned<T>>;
 pub type StringMap = Map<String>;
 
 /// A GitHub Actions workflow.
-#[allow(dead_code)]
 #[derive(Deserialize, Debug)]
 pub struct Workflow {
     /// The name of the workflow.
@@ -29,10 +35,10 @@ pub struct Workflow {
     ///
     /// Documentation: <https://docs.github.com/en/actions/writing-workflows/workflow-syntax-for-github-actions#run-name>
     pub run_name: Option<Spanned<String>>,
-    ///// Defines which events can cause the workflow to run.
-    /////
-    ///// Documentation: <https://docs.github.com/en/actions/writing-workflows/workflow-syntax-for-github-actions#on>
-    //on: Spanned<WorkflowTrigger>,
+    /// Defines which events can cause the workflow to run.
+    ///
+    /// Documentation: <https://docs.github.com/en/actions/writing-workflows/workflow-syntax-for-github-actions#on>
+    pub on: Spanned<WorkflowTrigger>,
     /// Sets the default permissions granted to the `GITHUB_TOKEN`.
     ///
     /// Documentation: <https://docs.github.com/en/actions/writing-workflows/workflow-syntax-for-github-actions#permissions>
@@ -41,18 +47,21 @@ pub struct Workflow {
     ///
     /// Documentation: <https://docs.github.com/en/actions/writing-workflows/workflow-syntax-for-github-actions#env>
     pub env: Option<Spanned<StringMap>>,
-    ///// Default settings that will apply to all jobs in the workflow.
-    /////
-    ///// Documentation: <https://docs.github.com/en/actions/writing-workflows/workflow-syntax-for-github-actions#defaults>
-    //pub defaults: Option<Spanned<Node>>,
-    ///// Sets a concurrency group that ensures that only a single job or workflow using the same group will run at a time.
-    /////
-    ///// Documentation: <https://docs.github.com/en/actions/writing-workflows/workflow-syntax-for-github-actions#concurrency>
-    //pub concurrency: Option<Spanned<Node>>,
+    /// Default settings that will apply to all jobs in the workflow.
+    ///
+    /// Documentation: <https://docs.github.com/en/actions/writing-workflows/workflow-syntax-for-github-actions#defaults>
+    pub defaults: Option<Spanned<Defaults>>,
+    /// Sets a concurrency group that ensures that only a single job or workflow using the same group will run at a time.
+    ///
+    /// Documentation: <https://docs.github.com/en/actions/writing-workflows/workflow-syntax-for-github-actions#concurrency>
+    pub concurrency: Option<Spanned<StringOrMap<Concurrency>>>,
     /// Defines jobs that are part of this workflow.
     ///
     /// Documentation: <https://docs.github.com/en/actions/writing-workflows/workflow-syntax-for-github-actions#jobs>
     pub jobs: Spanned<Map<Job>>,
+    /// The YAML source of this workflow.
+    #[serde(skip)]
+    source: Source,
 }
 
 impl Workflow {
@@ -61,15 +70,26 @@ impl Workflow {
     where
         R: Read,
     {
-        let mut buffer = String::new();
-        reader.read_to_string(&mut buffer)?;
-        let workflow: Workflow = marked_yaml::from_yaml(0, &buffer)?;
+        let source = Source::from_reader(reader)?;
+        let mut workflow: Workflow = source.deserialize()?;
+        workflow.source = source;
         Ok(workflow)
     }
+
+    /// The YAML source of this workflow.
+    #[inline]
+    pub fn source(&self) -> &Source {
+        &self.source
+    }
+
+    /// The inputs, outputs and secrets of a reusable workflow (i.e., the configuration of the
+    /// `workflow_call` event), if it has any.
+    pub fn workflow_call(&self) -> Option<&EventFilter> {
+        self.on.get(&Event::WorkflowCall)?.filter()
+    }
 }
 
 /// Token Permission Settings
-#[allow(dead_code)]
 #[derive(Deserialize, Debug)]
 #[serde(try_from = "StringOrStruct<PermissionEvent>")]
 #[serde(rename_all = "kebab-case")]
@@ -106,8 +126,8 @@ impl FromStr for Permissions {
 }
 
 /// Fine-Grained Token Permissions
-#[allow(dead_code)]
 #[derive(Deserialize, Debug)]
+#[serde(rename_all = "kebab-case")]
 pub struct PermissionEvent {
     /// Work with GitHub Actions.
     actions: Option<PermissionLevel>,
@@ -125,6 +145,8 @@ pub struct PermissionEvent {
     id_token: Option<PermissionLevel>,
     /// Work with issues.
     issues: Option<PermissionLevel>,
+    /// Generate AI inference responses with GitHub Models.
+    models: Option<PermissionLevel>,
     /// Work with GitHub Packages.
     packages: Option<PermissionLevel>,
     /// Work with GitHub Pages.
@@ -180,6 +202,11 @@ impl PermissionEvent {
         self.issues.unwrap_or_default()
     }
 
+    /// Generate AI inference responses with GitHub Models.
+    pub fn models(&self) -> PermissionLevel {
+        self.models.unwrap_or_default()
+    }
+
     /// Work with GitHub Packages.
     pub fn packages(&self) -> PermissionLevel {
         self.packages.unwrap_or_default()
@@ -219,13 +246,14 @@ impl PermissionEvent {
             ("contents", self.contents()),
             ("deployments", self.deployments()),
             ("discussions", self.discussions()),
-            ("id_token", self.id_token()),
+            ("id-token", self.id_token()),
             ("issues", self.issues()),
+            ("models", self.models()),
             ("packages", self.packages()),
             ("pages", self.pages()),
-            ("pull_requests", self.pull_requests()),
-            ("repository_projects", self.repository_projects()),
-            ("security_events", self.security_events()),
+            ("pull-requests", self.pull_requests()),
+            ("repository-projects", self.repository_projects()),
+            ("security-events", self.security_events()),
             ("statuses", self.statuses()),
         ]
         .into_iter()
@@ -233,7 +261,6 @@ impl PermissionEvent {
 }
 
 /// Work with commit statuses.
-#[allow(dead_code)]
 #[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case")]
 pub enum PermissionLevel {
@@ -247,23 +274,441 @@ pub enum PermissionLevel {
 }
 
 /// A job in a GitHub workflow.
-#[allow(dead_code)]
+///
+/// Documentation: <https://docs.github.com/en/actions/writing-workflows/workflow-syntax-for-github-actions#jobs>
 #[derive(Deserialize, Debug)]
-#[serde(rename_all = "kebab-case")]
-pub struct Job {
+#[serde(try_from = "JobSyntax")]
+pub enum Job {
+    /// A job that runs a sequence of steps on a runner.
+    Normal(Box<NormalJob>),
+    /// A job that calls a reusable workflow.
+    Call(Box<ReusableWorkflowCallJob>),
+}
+
+impl Job {
+    /// The name of the job displayed on GitHub.
+    pub fn name(&self) -> Option<&Spanned<String>> {
+        match self {
+            Job::Normal(job) => job.name.as_ref(),
+            Job::Call(job) => job.name.as_ref(),
+        }
+    }
+
+    /// Jobs that must complete successfully before this job will run.
+    pub fn needs(&self) -> Option<&Spanned<StringOrList>> {
+        match self {
+            Job::Normal(job) => job.needs.as_ref(),
+            Job::Call(job) => job.needs.as_ref(),
+        }
+    }
+
+    /// A conditional to prevent a job from running unless a condition is met.
+    pub fn condition(&self) -> Option<&Spanned<String>> {
+        match self {
+            Job::Normal(job) => job.condition.as_ref(),
+            Job::Call(job) => job.condition.as_ref(),
+        }
+    }
+
+    /// Token permissions for the job.
+    pub fn permissions(&self) -> Option<&Spanned<Permissions>> {
+        match self {
+            Job::Normal(job) => job.permissions.as_ref(),
+            Job::Call(job) => job.permissions.as_ref(),
+        }
+    }
+
+    /// Ensures that only a single job or workflow using the same concurrency group will run at a
+    /// time.
+    pub fn concurrency(&self) -> Option<&Spanned<StringOrMap<Concurrency>>> {
+        match self {
+            Job::Normal(job) => job.concurrency.as_ref(),
+            Job::Call(job) => job.concurrency.as_ref(),
+        }
+    }
+
+    /// A matrix strategy that creates multiple job runs.
+    pub fn strategy(&self) -> Option<&Spanned<Strategy>> {
+        match self {
+            Job::Normal(job) => job.strategy.as_ref(),
+            Job::Call(job) => job.strategy.as_ref(),
+        }
+    }
+
+    /// The steps of the job (reusable workflow calls do not have any).
+    pub fn steps(&self) -> Option<&Spanned<Vec<Step>>> {
+        match self {
+            Job::Normal(job) => job.steps.as_ref(),
+            Job::Call(_) => None,
+        }
+    }
+}
+
+/// A job that runs a sequence of steps on a runner.
+///
+/// Documentation: <https://docs.github.com/en/actions/writing-workflows/workflow-syntax-for-github-actions#jobs>
+#[derive(Debug)]
+pub struct NormalJob {
+    /// The name of the job displayed on GitHub.
+    pub name: Option<Spanned<String>>,
+    /// Jobs that must complete successfully before this job will run.
+    pub needs: Option<Spanned<StringOrList>>,
+    /// A conditional to prevent a job from running unless a condition is met.
+    pub condition: Option<Spanned<String>>,
     /// Token permissions for the job.
     pub permissions: Option<Spanned<Permissions>>,
     /// Defines the type of machine to run the job on.
-    pub runs_on: Spanned<String>,
+    pub runs_on: Spanned<RunsOn>,
+    /// The environment that the job references.
+    pub environment: Option<Spanned<StringOrMap<Environment>>>,
+    /// Ensures that only a single job or workflow using the same concurrency group will run at a
+    /// time.
+    pub concurrency: Option<Spanned<StringOrMap<Concurrency>>>,
+    /// Outputs that are available to all downstream jobs that depend on this job.
+    pub outputs: Option<Spanned<StringMap>>,
+    /// Environment variables that are available to all steps in the job.
+    pub env: Option<Spanned<StringMap>>,
+    /// Default settings that will apply to all steps in the job.
+    pub defaults: Option<Spanned<Defaults>>,
     /// Override the default shell settings in the runner's operating system and sets it as the
     /// job's default.
     pub shell: Option<Spanned<String>>,
     /// A sequence of tasks that are run for this job.
     pub steps: Option<Spanned<Vec<Step>>>,
+    /// The maximum number of minutes to let a job run before GitHub automatically cancels it.
+    pub timeout_minutes: Option<Spanned<ValueOrExpression<f64>>>,
+    /// A matrix strategy that creates multiple job runs.
+    pub strategy: Option<Spanned<Strategy>>,
+    /// Prevents a workflow run from failing when this job fails.
+    pub continue_on_error: Option<Spanned<ValueOrExpression<bool>>>,
+    /// A container to run the steps of the job in.
+    pub container: Option<Spanned<StringOrMap<Container>>>,
+    /// Service containers to host services for the job.
+    pub services: Option<Spanned<Map<Container>>>,
+}
+
+/// A job that calls a reusable workflow.
+///
+/// Documentation: <https://docs.github.com/en/actions/writing-workflows/workflow-syntax-for-github-actions#jobsjob_iduses>
+#[derive(Debug)]
+pub struct ReusableWorkflowCallJob {
+    /// The name of the job displayed on GitHub.
+    pub name: Option<Spanned<String>>,
+    /// Jobs that must complete successfully before this job will run.
+    pub needs: Option<Spanned<StringOrList>>,
+    /// A conditional to prevent a job from running unless a condition is met.
+    pub condition: Option<Spanned<String>>,
+    /// Token permissions for the job.
+    pub permissions: Option<Spanned<Permissions>>,
+    /// Ensures that only a single job or workflow using the same concurrency group will run at a
+    /// time.
+    pub concurrency: Option<Spanned<StringOrMap<Concurrency>>>,
+    /// A matrix strategy that creates multiple job runs.
+    pub strategy: Option<Spanned<Strategy>>,
+    /// The reusable workflow to call, e.g. `octo-org/repo/.github/workflows/ci.yml@v1` or
+    /// `./.github/workflows/ci.yml`.
+    pub uses: Spanned<ActionRef>,
+    /// Inputs that are passed to the called workflow.
+    pub with: Option<Spanned<StringMap>>,
+    /// Secrets that are passed to the called workflow.
+    pub secrets: Option<Spanned<JobSecrets>>,
+}
+
+/// Secrets that are passed to a reusable workflow.
+///
+/// Documentation: <https://docs.github.com/en/actions/writing-workflows/workflow-syntax-for-github-actions#jobsjob_idsecrets>
+#[derive(Deserialize, Debug)]
+#[serde(try_from = "StringOrMap<StringMap>")]
+pub enum JobSecrets {
+    /// All secrets of the calling workflow are passed (`secrets: inherit`).
+    Inherit,
+    /// Only the listed secrets are passed.
+    Map(StringMap),
+}
+
+impl TryFrom<StringOrMap<StringMap>> for JobSecrets {
+    type Error = String;
+
+    fn try_from(value: StringOrMap<StringMap>) -> Result<Self, Self::Error> {
+        match value {
+            StringOrMap::String(value) if value == "inherit" => Ok(JobSecrets::Inherit),
+            StringOrMap::String(value) => Err(format!(
+                "invalid secrets {:?} (expected 'inherit' or a map)",
+                value
+            )),
+            StringOrMap::Map(secrets) => Ok(JobSecrets::Map(secrets)),
+        }
+    }
+}
+
+/// The union of the fields of all kinds of jobs, which is used to deserialize a [`Job`] without
+/// losing the spans of nested values.
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct JobSyntax {
+    name: Option<Spanned<String>>,
+    needs: Option<Spanned<StringOrList>>,
+    #[serde(rename = "if")]
+    condition: Option<Spanned<String>>,
+    permissions: Option<Spanned<Permissions>>,
+    runs_on: Option<Spanned<RunsOn>>,
+    environment: Option<Spanned<StringOrMap<Environment>>>,
+    concurrency: Option<Spanned<StringOrMap<Concurrency>>>,
+    outputs: Option<Spanned<StringMap>>,
+    env: Option<Spanned<StringMap>>,
+    defaults: Option<Spanned<Defaults>>,
+    shell: Option<Spanned<String>>,
+    steps: Option<Spanned<Vec<Step>>>,
+    timeout_minutes: Option<Spanned<ValueOrExpression<f64>>>,
+    strategy: Option<Spanned<Strategy>>,
+    continue_on_error: Option<Spanned<ValueOrExpression<bool>>>,
+    container: Option<Spanned<StringOrMap<Container>>>,
+    services: Option<Spanned<Map<Container>>>,
+    uses: Option<Spanned<ActionRef>>,
+    with: Option<Spanned<StringMap>>,
+    secrets: Option<Spanned<JobSecrets>>,
+}
+
+impl TryFrom<JobSyntax> for Job {
+    type Error = String;
+
+    fn try_from(job: JobSyntax) -> Result<Self, Self::Error> {
+        if let Some(uses) = job.uses {
+            if job.runs_on.is_some() || job.steps.is_some() {
+                return Err(
+                    "a job that calls a reusable workflow cannot have 'runs-on' or 'steps'"
+                        .to_owned(),
+                );
+            }
+            return Ok(Job::Call(Box::new(ReusableWorkflowCallJob {
+                name: job.name,
+                needs: job.needs,
+                condition: job.condition,
+                permissions: job.permissions,
+                concurrency: job.concurrency,
+                strategy: job.strategy,
+                uses,
+                with: job.with,
+                secrets: job.secrets,
+            })));
+        }
+
+        let runs_on = job.runs_on.ok_or("missing field `runs-on`")?;
+        Ok(Job::Normal(Box::new(NormalJob {
+            name: job.name,
+            needs: job.needs,
+            condition: job.condition,
+            permissions: job.permissions,
+            runs_on,
+            environment: job.environment,
+            concurrency: job.concurrency,
+            outputs: job.outputs,
+            env: job.env,
+            defaults: job.defaults,
+            shell: job.shell,
+            steps: job.steps,
+            timeout_minutes: job.timeout_minutes,
+            strategy: job.strategy,
+            continue_on_error: job.continue_on_error,
+            container: job.container,
+            services: job.services,
+        })))
+    }
+}
+
+/// Defines the type of machine to run a job on.
+///
+/// Documentation: <https://docs.github.com/en/actions/writing-workflows/workflow-syntax-for-github-actions#jobsjob_idruns-on>
+#[derive(Debug)]
+pub enum RunsOn {
+    /// A single runner label, e.g. `ubuntu-latest`.
+    Label(String),
+    /// Multiple runner labels, e.g. `[self-hosted, linux]`.
+    Labels(Vec<Spanned<String>>),
+    /// A runner group.
+    Group(RunnerGroup),
+}
+
+impl<'de> Deserialize<'de> for RunsOn {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct RunsOnVisitor;
+
+        impl<'de> Visitor<'de> for RunsOnVisitor {
+            type Value = RunsOn;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a runner label, a list of runner labels or a runner group")
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(RunsOn::Label(value.to_owned()))
+            }
+
+            fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                Vec::deserialize(SeqAccessDeserializer::new(seq)).map(RunsOn::Labels)
+            }
+
+            fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                RunnerGroup::deserialize(MapAccessDeserializer::new(map)).map(RunsOn::Group)
+            }
+        }
+
+        deserializer.deserialize_any(RunsOnVisitor)
+    }
+}
+
+/// A runner group and optional labels.
+#[derive(Deserialize, Debug)]
+pub struct RunnerGroup {
+    /// The name of the runner group.
+    pub group: Option<Spanned<String>>,
+    /// Runner labels.
+    pub labels: Option<Spanned<StringOrList>>,
+}
+
+/// A deployment environment.
+///
+/// Documentation: <https://docs.github.com/en/actions/writing-workflows/workflow-syntax-for-github-actions#jobsjob_idenvironment>
+#[derive(Deserialize, Debug)]
+pub struct Environment {
+    /// The name of the environment.
+    pub name: Spanned<String>,
+    /// The URL of the deployment.
+    pub url: Option<Spanned<String>>,
+}
+
+impl StringOrMap<Environment> {
+    /// The name of the environment.
+    pub fn name(&self) -> &str {
+        match self {
+            StringOrMap::String(name) => name,
+            StringOrMap::Map(environment) => &environment.name,
+        }
+    }
+}
+
+/// A concurrency group.
+///
+/// Documentation: <https://docs.github.com/en/actions/writing-workflows/workflow-syntax-for-github-actions#concurrency>
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub struct Concurrency {
+    /// The name of the concurrency group.
+    pub group: Spanned<String>,
+    /// Cancel any currently running job or workflow in the same concurrency group.
+    pub cancel_in_progress: Option<Spanned<ValueOrExpression<bool>>>,
+}
+
+/// Default settings for jobs or steps.
+///
+/// Documentation: <https://docs.github.com/en/actions/writing-workflows/workflow-syntax-for-github-actions#defaults>
+#[derive(Deserialize, Debug)]
+pub struct Defaults {
+    /// Default settings for `run` steps.
+    pub run: Option<Spanned<RunDefaults>>,
+}
+
+/// Default settings for `run` steps.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub struct RunDefaults {
+    /// The default shell.
+    pub shell: Option<Spanned<String>>,
+    /// The default working directory.
+    pub working_directory: Option<Spanned<String>>,
+}
+
+/// A matrix strategy.
+///
+/// Documentation: <https://docs.github.com/en/actions/writing-workflows/workflow-syntax-for-github-actions#jobsjob_idstrategy>
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub struct Strategy {
+    /// The matrix of job configurations (or an expression that evaluates to one).
+    pub matrix: Option<Spanned<StringOrMap<Matrix>>>,
+    /// Cancel all in-progress and queued jobs in the matrix if any job in the matrix fails.
+    pub fail_fast: Option<Spanned<ValueOrExpression<bool>>>,
+    /// The maximum number of jobs that can run simultaneously.
+    pub max_parallel: Option<Spanned<ValueOrExpression<u32>>>,
+}
+
+/// The variables of a matrix strategy.
+///
+/// Apart from the special `include` and `exclude` keys, every key is a matrix dimension.
+#[derive(Deserialize, Debug)]
+#[serde(transparent)]
+pub struct Matrix(Map<Value>);
+
+impl Matrix {
+    /// Additional matrix configurations.
+    pub fn include(&self) -> Option<&Spanned<Value>> {
+        self.0.get("include")
+    }
+
+    /// Matrix configurations that are removed.
+    pub fn exclude(&self) -> Option<&Spanned<Value>> {
+        self.0.get("exclude")
+    }
+
+    /// Iterates over the matrix dimensions and their values.
+    pub fn dimensions(&self) -> impl Iterator<Item = (&String, &Spanned<Value>)> {
+        self.0
+            .iter()
+            .filter(|(key, _value)| !matches!(key.as_str(), "include" | "exclude"))
+    }
+}
+
+/// A container to run a job (or a service) in.
+///
+/// Documentation: <https://docs.github.com/en/actions/writing-workflows/workflow-syntax-for-github-actions#jobsjob_idcontainer>
+#[derive(Deserialize, Debug)]
+pub struct Container {
+    /// The Docker image to use.
+    pub image: Spanned<String>,
+    /// Credentials for the container registry.
+    pub credentials: Option<Spanned<Credentials>>,
+    /// Environment variables in the container.
+    pub env: Option<Spanned<StringMap>>,
+    /// Ports to expose on the container.
+    pub ports: Option<Spanned<Vec<Spanned<String>>>>,
+    /// Volumes for the container to use.
+    pub volumes: Option<Spanned<Vec<Spanned<String>>>>,
+    /// Additional Docker container resource options.
+    pub options: Option<Spanned<String>>,
+}
+
+impl StringOrMap<Container> {
+    /// The Docker image to use.
+    pub fn image(&self) -> &str {
+        match self {
+            StringOrMap::String(image) => image,
+            StringOrMap::Map(container) => &container.image,
+        }
+    }
+}
+
+/// Credentials for a container registry.
+#[derive(Deserialize, Debug)]
+pub struct Credentials {
+    /// The username.
+    pub username: Spanned<String>,
+    /// The password.
+    pub password: Spanned<String>,
 }
 
 /// A task that is run as part of Job.
-#[allow(dead_code)]
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "kebab-case")]
 pub struct Step {
@@ -275,7 +720,7 @@ pub struct Step {
     /// A name for your step to display on GitHub.
     pub name: Option<Spanned<String>>,
     /// Selects an action to run as part of a step in the job.
-    pub uses: Option<Spanned<String>>,
+    pub uses: Option<Spanned<ActionRef>>,
     /// Runs command-line programs using the operating system's shell.
     pub run: Option<Spanned<String>>,
     /// Specifies the working directory of where to run the command.
@@ -287,6 +732,136 @@ pub struct Step {
     pub with: Option<Spanned<StringMap>>,
     /// Sets variables for steps to use in the runner environment.
     pub env: Option<Spanned<StringMap>>,
-    //pub continue_on_error: Option<Spanned<ContinueOnError>>,
-    //pub timeout_minutes: Option<Spanned<Timeout>>,
+    /// Prevents a job from failing when this step fails.
+    pub continue_on_error: Option<Spanned<ValueOrExpression<bool>>>,
+    /// The maximum number of minutes to run the step before killing the process.
+    pub timeout_minutes: Option<Spanned<ValueOrExpression<f64>>>,
+}
+
+impl Step {
+    /// A key that identifies the step within its job: the `id` (if set) or the 1-based index.
+    ///
+    /// Unlike the index, the `id` does not change if other steps are added or removed.
+    pub fn key(&self, index: usize) -> String {
+        self.id
+            .as_ref()
+            .map_or_else(|| (index + 1).to_string(), |id| id.to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_job() {
+        let source = r#"
+on: push
+jobs:
+  deploy:
+    needs: [build, test]
+    if: github.ref == 'refs/heads/main'
+    runs-on: [self-hosted, linux]
+    environment:
+      name: production
+      url: https://example.com
+    concurrency:
+      group: deploy
+      cancel-in-progress: ${{ github.ref != 'refs/heads/main' }}
+    timeout-minutes: 30
+    continue-on-error: true
+    strategy:
+      fail-fast: false
+      max-parallel: 2
+      matrix:
+        os: [ubuntu-latest, windows-latest]
+        include:
+          - os: macos-latest
+    container:
+      image: ghcr.io/foo/bar:1.0
+      credentials:
+        username: foo
+        password: ${{ secrets.PASSWORD }}
+    services:
+      redis:
+        image: redis:7
+    steps:
+      - run: make deploy
+"#;
+        let workflow = Workflow::from_reader(&mut source.as_bytes()).unwrap();
+        let Job::Normal(job) = &*workflow.jobs["deploy"] else {
+            panic!("expected a normal job");
+        };
+        let needs = job.needs.as_ref().unwrap();
+        assert!(needs.contains("build") && needs.contains("test"));
+        assert!(matches!(&*job.runs_on, RunsOn::Labels(labels) if labels.len() == 2));
+        assert_eq!(job.environment.as_ref().unwrap().name(), "production");
+        assert_eq!(job.timeout_minutes.as_ref().unwrap().value(), Some(30.0));
+        assert_eq!(job.continue_on_error.as_ref().unwrap().value(), Some(true));
+        let concurrency = job.concurrency.as_ref().unwrap().as_map().unwrap();
+        assert_eq!(
+            **concurrency.cancel_in_progress.as_ref().unwrap(),
+            ValueOrExpression::Expression("${{ github.ref != 'refs/heads/main' }}".to_owned())
+        );
+
+        let strategy = job.strategy.as_ref().unwrap();
+        assert_eq!(strategy.fail_fast.as_ref().unwrap().value(), Some(false));
+        assert_eq!(strategy.max_parallel.as_ref().unwrap().value(), Some(2));
+        let matrix = strategy.matrix.as_ref().unwrap().as_map().unwrap();
+        assert_eq!(
+            matrix
+                .dimensions()
+                .map(|(key, _)| key.as_str())
+                .collect::<Vec<_>>(),
+            vec!["os"]
+        );
+        assert!(matrix.include().is_some() && matrix.exclude().is_none());
+
+        let container = job.container.as_ref().unwrap();
+        assert_eq!(container.image(), "ghcr.io/foo/bar:1.0");
+        let credentials = container.as_map().unwrap().credentials.as_ref().unwrap();
+        assert_eq!(credentials.password.span().start().unwrap().line(), 27);
+        assert_eq!(
+            job.services.as_ref().unwrap()["redis"].image.as_str(),
+            "redis:7"
+        );
+    }
+
+    #[test]
+    fn test_parse_reusable_workflow_call_job() {
+        let source = r#"
+on: push
+jobs:
+  call-inherit:
+    uses: octo-org/example-repo/.github/workflows/reusable.yml@main
+    secrets: inherit
+  call-local:
+    needs: call-inherit
+    uses: ./.github/workflows/reusable.yml
+    with:
+      config-path: .github/labeler.yml
+    secrets:
+      token: ${{ secrets.TOKEN }}
+"#;
+        let workflow = Workflow::from_reader(&mut source.as_bytes()).unwrap();
+        let Job::Call(job) = &*workflow.jobs["call-inherit"] else {
+            panic!("expected a reusable workflow call job");
+        };
+        assert!(job.uses.is_repository("octo-org/example-repo"));
+        assert!(matches!(job.secrets.as_deref(), Some(JobSecrets::Inherit)));
+
+        let Job::Call(job) = &*workflow.jobs["call-local"] else {
+            panic!("expected a reusable workflow call job");
+        };
+        assert!(matches!(*job.uses, ActionRef::Local { .. }));
+        assert!(job.with.as_ref().unwrap().contains_key("config-path"));
+        assert!(matches!(
+            job.secrets.as_deref(),
+            Some(JobSecrets::Map(secrets)) if secrets.contains_key("token")
+        ));
+        assert!(workflow.jobs["call-local"].steps().is_none());
+
+        let source = "on: push\njobs:\n  build:\n    steps: []\n";
+        assert!(Workflow::from_reader(&mut source.as_bytes()).is_err());
+    }
 }
@@ -0,0 +1,193 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+use super::PolicyViolation;
+use crate::parser::action::Runs;
+use crate::parser::action_ref::ActionRef;
+use crate::parser::document::Document;
+use crate::parser::workflow::Job;
+use ghastly_macros::policy;
+use serde::Deserialize;
+
+/// Options of the [`actions_pinned`] policy.
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct ActionsPinnedOptions {
+    /// Actions that do not need to be pinned, as patterns accepted by [`ActionRef::matches`]
+    /// (e.g. `actions/*`).
+    ///
+    /// By default, no action is trusted.
+    trusted_actions: Vec<String>,
+}
+
+impl ActionsPinnedOptions {
+    /// Returns `true` if the action (or reusable workflow) is pinned or trusted.
+    fn is_pinned_or_trusted(&self, uses: &ActionRef) -> bool {
+        uses.is_pinned()
+            || self
+                .trusted_actions
+                .iter()
+                .any(|pattern| uses.matches(pattern))
+    }
+}
+
+#[policy(severity = "medium", confidence = "high", category = "supply-chain")]
+/// Every action should be pinned to a full commit SHA.
+///
+/// Tags and branches are mutable, i.e., the owner of an action (or anyone who compromised their
+/// account) can change the code that is executed by your workflow at any time. Pinning an action
+/// to a full-length commit SHA is currently the only way to use an action as an immutable
+/// release. Similarly, Docker actions should be pinned to an image digest. The same applies to
+/// reusable workflows that are called from other repositories.
+///
+/// Local actions (e.g. `./.github/actions/foo`) and local reusable workflows are versioned
+/// together with the workflow and do not need to be pinned.
+///
+/// Steps of composite actions are checked as well, and so is the image of Docker actions that
+/// use a public registry image (`docker://...`) instead of a `Dockerfile`.
+///
+/// # Options
+///
+/// Options are set in the `[policies.actions_pinned]` table of the configuration file
+/// (`.ghastly.toml`).
+///
+/// - `trusted-actions`: Actions that do not need to be pinned, e.g. `["actions/*"]` (all actions
+///   of an owner), `["actions/checkout"]` (all actions in a repository) or
+///   `["github/codeql-action/init"]` (a single action).
+///
+/// # Examples
+///
+/// ## Not OK: Action pinned to a Tag
+///
+/// ```yaml
+/// on: [push]
+/// jobs:
+///   build:
+///     runs-on: ubuntu-latest
+///     permissions:
+///       contents: read
+///     steps:
+///       - uses: actions/checkout@v4
+/// ```
+///
+/// ## Not OK: Docker Action without Digest
+///
+/// ```yaml
+/// on: [push]
+/// jobs:
+///   build:
+///     runs-on: ubuntu-latest
+///     permissions:
+///       contents: read
+///     steps:
+///       - uses: docker://alpine:3.21
+/// ```
+///
+/// ## Not OK: Reusable Workflow pinned to a Branch
+///
+/// ```yaml
+/// on: [push]
+/// jobs:
+///   build:
+///     permissions:
+///       contents: read
+///     uses: octo-org/workflows/.github/workflows/build.yml@main
+/// ```
+///
+/// ## OK: Action pinned to a Commit SHA
+///
+/// ```yaml
+/// on: [push]
+/// jobs:
+///   build:
+///     runs-on: ubuntu-latest
+///     permissions:
+///       contents: read
+///     steps:
+///       - uses: actions/checkout@11bd71901bbe5b1630ceea73d27597364c9af683 # v4.2.2
+///       - uses: ./.github/actions/build
+/// ```
+///
+/// ## Not OK: Composite Action uses Action pinned to a Tag
+///
+/// ```yaml
+/// name: Build
+/// runs:
+///   using: composite
+///   steps:
+///     - uses: actions/setup-node@v4
+/// ```
+///
+/// ## Not OK: Docker Action uses Image without Digest
+///
+/// ```yaml
+/// name: Lint
+/// runs:
+///   using: docker
+///   image: docker://alpine:3.21
+/// ```
+///
+/// # References
+///
+/// - <https://docs.github.com/en/actions/security-for-github-actions/security-guides/security-hardening-for-github-actions#using-third-party-actions>
+pub fn actions_pinned(document: &Document, options: &ActionsPinnedOptions) -> Vec<PolicyViolation> {
+    let calls = document
+        .as_workflow()
+        .into_iter()
+        .flat_map(|workflow| workflow.jobs.iter())
+        .filter_map(|(job_name, job)| match &**job {
+            Job::Call(call) if !options.is_pinned_or_trusted(&call.uses) => Some(
+                PolicyViolation::new(
+                    document
+                        .source()
+                        .scalar_location(&call.uses, &call.uses.to_string()),
+                    format!(
+                        "Job {} should pin reusable workflow '{}' to a full commit SHA.",
+                        job_name, *call.uses
+                    ),
+                )
+                .with_job(job_name),
+            ),
+            _ => None,
+        });
+    let steps = document.steps().into_iter().filter_map(|step| {
+        let uses = step.step.uses.as_ref()?;
+        if options.is_pinned_or_trusted(uses) {
+            return None;
+        }
+        Some(
+            PolicyViolation::new(
+                document.source().scalar_location(uses, &uses.to_string()),
+                format!(
+                    "{} should pin action '{}' to a full commit SHA.",
+                    step.description(),
+                    **uses
+                ),
+            )
+            .with_document_step(&step)
+            .with_key(uses.to_string()),
+        )
+    });
+    let image = document
+        .as_action()
+        .and_then(|action| match &*action.runs {
+            Runs::Docker(runs) => Some(&runs.image),
+            Runs::Composite(_) | Runs::JavaScript(_) => None,
+        })
+        .filter(|image| image.starts_with("docker://"))
+        .and_then(|image| {
+            let uses: ActionRef = image.parse().ok()?;
+            (!options.is_pinned_or_trusted(&uses)).then(|| {
+                PolicyViolation::new(
+                    document.source().scalar_location(image, image),
+                    format!("The Docker action should pin image '{uses}' to a digest."),
+                )
+            })
+        });
+    calls.chain(steps).chain(image).collect()
+}
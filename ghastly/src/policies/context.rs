@@ -0,0 +1,62 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+use super::known_actions::{find_known_action, KnownAction};
+use super::PolicyOptions;
+use crate::parser::action_ref::ActionRef;
+use crate::Repository;
+use std::path::Path;
+
+/// The context in which a policy checks a document.
+///
+/// Besides the document itself, policies may depend on their options, the path of the checked
+/// file, other files in the repository and facts about well-known actions.
+#[derive(Debug, Clone, Copy)]
+pub struct PolicyContext<'a> {
+    path: &'a Path,
+    options: &'a PolicyOptions,
+    repository: &'a Repository,
+}
+
+impl<'a> PolicyContext<'a> {
+    pub const fn new(
+        path: &'a Path,
+        options: &'a PolicyOptions,
+        repository: &'a Repository,
+    ) -> Self {
+        Self {
+            path,
+            options,
+            repository,
+        }
+    }
+
+    /// The path of the checked file.
+    #[inline]
+    pub const fn path(&self) -> &'a Path {
+        self.path
+    }
+
+    /// The options of the policy, as set in the configuration file.
+    #[inline]
+    pub const fn options(&self) -> &'a PolicyOptions {
+        self.options
+    }
+
+    /// The workflows and actions of the repository that contains the checked file.
+    #[inline]
+    pub const fn repository(&self) -> &'a Repository {
+        self.repository
+    }
+
+    /// Facts about the action, if it is a well-known action.
+    #[inline]
+    pub fn known_action(&self, uses: &ActionRef) -> Option<&'static KnownAction> {
+        find_known_action(uses)
+    }
+}
@@ -6,20 +6,460 @@
 //
 // SPDX-License-Identifier: MPL-2.0
 
-use super::PolicyViolation;
+use super::{PolicyContext, PolicyViolation, Severity};
+use crate::fix::{Edit, Fix};
 use crate::parser::{
-    expression::{tokenize, TokenKind},
-    workflow::Workflow,
+    action::Runs,
+    document::{Document, DocumentStep},
+    expression::{parse_expression, parse_template, tokenize, BinaryOp, Expr, ExprKind, TokenKind},
+    location::key_start,
+    workflow::{Defaults, Job, NormalJob, RunsOn},
 };
+use crate::{Repository, WorkflowCall, MAX_CALL_DEPTH};
 use ghastly_macros::policy;
+use marked_yaml::Spanned;
+use std::path::Path;
 
-#[policy]
+/// Contexts that can be fully controlled by an attacker, e.g. by opening a pull request or
+/// creating an issue. A `*` matches any single path segment.
+///
+/// Based on <https://securitylab.github.com/resources/github-actions-untrusted-input/>.
+const UNTRUSTED_CONTEXTS: &[&str] = &[
+    "github.head_ref",
+    "github.event.issue.title",
+    "github.event.issue.body",
+    "github.event.pull_request.title",
+    "github.event.pull_request.body",
+    "github.event.pull_request.head.ref",
+    "github.event.pull_request.head.label",
+    "github.event.pull_request.head.repo.default_branch",
+    "github.event.comment.body",
+    "github.event.review.body",
+    "github.event.review_comment.body",
+    "github.event.discussion.title",
+    "github.event.discussion.body",
+    "github.event.pages.*.page_name",
+    "github.event.commits.*.message",
+    "github.event.commits.*.author.email",
+    "github.event.commits.*.author.name",
+    "github.event.head_commit.message",
+    "github.event.head_commit.author.email",
+    "github.event.head_commit.author.name",
+    "github.event.workflow_run.head_branch",
+    "github.event.workflow_run.head_commit.message",
+    "github.event.workflow_run.head_commit.author.email",
+    "github.event.workflow_run.head_commit.author.name",
+    "github.event.workflow_run.pull_requests.*.head.ref",
+];
+
+/// Contexts that may contain attacker-controlled data, depending on who can trigger the workflow
+/// or how the value was produced.
+const SUSPICIOUS_CONTEXTS: &[&str] = &["inputs.*", "github.event.inputs.*"];
+
+/// Functions whose result cannot be used to inject arbitrary text.
+const SAFE_FUNCTIONS: &[&str] = &[
+    "always",
+    "cancelled",
+    "contains",
+    "endswith",
+    "failure",
+    "hashfiles",
+    "startswith",
+    "success",
+];
+
+/// How much an expression can be controlled by an attacker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Taint {
+    /// The value may be attacker-controlled.
+    Suspicious,
+    /// The value is attacker-controlled.
+    Untrusted,
+}
+
+/// Returns `true` if the context path matches the pattern. Both are split into dot-separated
+/// segments, where `*` in the pattern matches any segment (including array indices).
+pub(super) fn context_matches(pattern: &str, path: &str) -> bool {
+    let path = path.replace('[', ".[");
+    let mut pattern_segments = pattern.split('.');
+    let mut path_segments = path.split('.');
+    loop {
+        match (pattern_segments.next(), path_segments.next()) {
+            (None, None) => return true,
+            (Some("*"), Some(_)) => continue,
+            (Some(expected), Some(actual)) if expected == actual => continue,
+            _ => return false,
+        }
+    }
+}
+
+/// Classifies a context path.
+///
+/// Paths that refer to an object containing untrusted data (e.g. `toJSON(github.event)`) are
+/// considered suspicious.
+fn classify_context(path: &str) -> Option<Taint> {
+    if UNTRUSTED_CONTEXTS
+        .iter()
+        .any(|pattern| context_matches(pattern, path))
+    {
+        return Some(Taint::Untrusted);
+    }
+
+    let is_parent_of_untrusted = UNTRUSTED_CONTEXTS.iter().any(|pattern| {
+        let depth = path.replace('[', ".[").split('.').count();
+        let prefix = pattern.split('.').take(depth).collect::<Vec<_>>().join(".");
+        depth < pattern.split('.').count() && context_matches(&prefix, path)
+    });
+    if is_parent_of_untrusted
+        || SUSPICIOUS_CONTEXTS
+            .iter()
+            .any(|pattern| context_matches(pattern, path))
+    {
+        return Some(Taint::Suspicious);
+    }
+
+    None
+}
+
+/// Collects all context accesses whose value can end up in the result of the expression.
+fn tainted_contexts(expr: &Expr) -> Vec<(Taint, String, &Expr)> {
+    if let Some(path) = expr.context_path() {
+        return classify_context(&path)
+            .map(|taint| vec![(taint, path, expr)])
+            .unwrap_or_default();
+    }
+
+    match &expr.kind {
+        // These always evaluate to booleans.
+        ExprKind::Not { .. } => vec![],
+        ExprKind::Binary { op, .. } if !matches!(op, BinaryOp::And | BinaryOp::Or) => vec![],
+        ExprKind::Call { function, .. }
+            if SAFE_FUNCTIONS.contains(&function.to_lowercase().as_str()) =>
+        {
+            vec![]
+        }
+        // The index only selects which value is returned.
+        ExprKind::Index { object, .. } => tainted_contexts(object),
+        _ => expr
+            .children()
+            .into_iter()
+            .flat_map(tainted_contexts)
+            .collect(),
+    }
+}
+
+/// Follows an `inputs.*` context of a reusable workflow to the values that the calling jobs in the
+/// repository pass to the input, and returns the first attacker-controlled context among them
+/// (together with the job that passes it).
+///
+/// Inputs that are set to inputs of the calling workflow are followed as well.
+fn untrusted_input_source<'a>(
+    repository: &'a Repository,
+    path: &Path,
+    context_path: &str,
+    depth: usize,
+) -> Option<(String, WorkflowCall<'a>)> {
+    let input = context_path
+        .strip_prefix("inputs.")?
+        .split(['.', '['])
+        .next()?;
+    if depth >= MAX_CALL_DEPTH {
+        return None;
+    }
+    repository.callers(path).into_iter().find_map(|call| {
+        parse_template(call.input(input)?)
+            .filter_map(Result::ok)
+            .find_map(|expr| {
+                tainted_contexts(&expr)
+                    .into_iter()
+                    .find_map(|(taint, source, _expr)| match taint {
+                        Taint::Untrusted => Some((source, call)),
+                        Taint::Suspicious => {
+                            untrusted_input_source(repository, call.path, &source, depth + 1)
+                        }
+                    })
+            })
+    })
+}
+
+/// Environment variables that should not be overridden by the fix of [`no_github_expr_in_run`].
+const RESERVED_ENV_NAMES: &[&str] = &["CI", "HOME", "PATH", "PWD", "SHELL", "USER"];
+
+/// A shell that is supported by the fix of [`no_github_expr_in_run`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Shell {
+    /// `bash` or `sh`.
+    Posix,
+    /// `pwsh` or `powershell`.
+    PowerShell,
+}
+
+/// The kind of quotes around a position in a script.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Quoting {
+    Unquoted,
+    Single,
+    Double,
+}
+
+impl Shell {
+    /// Determines the shell that runs the step, if it is known.
+    ///
+    /// Steps of composite actions have to set the shell explicitly.
+    fn of_step(document: &Document, step: &DocumentStep) -> Option<Shell> {
+        fn default_shell(defaults: &Option<Spanned<Defaults>>) -> Option<&str> {
+            Some(defaults.as_ref()?.run.as_ref()?.shell.as_ref()?.as_str())
+        }
+
+        let job = normal_job(step);
+        let shell = step
+            .step
+            .shell
+            .as_ref()
+            .map(|shell| shell.as_str())
+            .or_else(|| job.and_then(|job| default_shell(&job.defaults)))
+            .or_else(|| {
+                document
+                    .as_workflow()
+                    .and_then(|workflow| default_shell(&workflow.defaults))
+            });
+        let name = match shell {
+            Some(shell) => shell.split_whitespace().next()?,
+            // Without an explicit shell, it depends on the operating system of the runner.
+            None => {
+                let labels: Vec<_> = match &*job?.runs_on {
+                    RunsOn::Label(label) => vec![label.as_str()],
+                    RunsOn::Labels(labels) => labels.iter().map(|label| label.as_str()).collect(),
+                    RunsOn::Group(group) => group
+                        .labels
+                        .iter()
+                        .flat_map(|labels| labels.iter(labels.span()).map(|(label, _)| label))
+                        .collect(),
+                };
+                if labels.is_empty() || labels.iter().any(|label| label.contains("${{")) {
+                    return None;
+                }
+                if labels
+                    .iter()
+                    .any(|label| label.to_lowercase().contains("windows"))
+                {
+                    "pwsh"
+                } else {
+                    "bash"
+                }
+            }
+        };
+        match name {
+            "bash" | "sh" => Some(Shell::Posix),
+            "pwsh" | "powershell" => Some(Shell::PowerShell),
+            _ => None,
+        }
+    }
+
+    /// Returns the quoting after the given part of a script.
+    ///
+    /// This is a heuristic that only knows about quotes, escapes and comments.
+    fn quoting_after(self, mut quoting: Quoting, text: &str) -> Quoting {
+        let escape = match self {
+            Shell::Posix => '\\',
+            Shell::PowerShell => '`',
+        };
+        let mut previous = ' ';
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            quoting = match (quoting, c) {
+                (Quoting::Unquoted | Quoting::Double, c) if c == escape => {
+                    chars.next();
+                    quoting
+                }
+                (Quoting::Unquoted, '#') if previous.is_whitespace() => {
+                    chars.by_ref().find(|&c| c == '\n');
+                    quoting
+                }
+                (Quoting::Unquoted, '\'') => Quoting::Single,
+                (Quoting::Unquoted, '"') => Quoting::Double,
+                (Quoting::Single, '\'') | (Quoting::Double, '"') => Quoting::Unquoted,
+                _ => quoting,
+            };
+            previous = c;
+        }
+        quoting
+    }
+
+    /// Returns the reference to the environment variable that replaces an expression.
+    fn variable(self, name: &str, quoting: Quoting) -> Option<String> {
+        match (self, quoting) {
+            (Shell::Posix, Quoting::Unquoted) => Some(format!("\"${{{name}}}\"")),
+            (Shell::Posix, Quoting::Double) => Some(format!("${{{name}}}")),
+            (Shell::Posix, Quoting::Single) => Some(format!("'\"${{{name}}}\"'")),
+            (Shell::PowerShell, Quoting::Unquoted | Quoting::Double) => {
+                Some(format!("$env:{name}"))
+            }
+            (Shell::PowerShell, Quoting::Single) => None,
+        }
+    }
+}
+
+/// Derives the name of an environment variable from an expression, e.g. `PULL_REQUEST_TITLE`
+/// for `github.event.pull_request.title`.
+fn env_name(expression: &str) -> String {
+    let Some(path) = parse_expression(expression)
+        .ok()
+        .and_then(|expr| expr.context_path())
+    else {
+        return "VALUE".to_owned();
+    };
+    let path = path
+        .strip_prefix("github.event.")
+        .or_else(|| path.strip_prefix("github."))
+        .unwrap_or(&path);
+    let mut name = String::new();
+    for c in path.chars() {
+        if c.is_ascii_alphanumeric() {
+            name.push(c.to_ascii_uppercase());
+        } else if !name.is_empty() && !name.ends_with('_') {
+            name.push('_');
+        }
+    }
+    let name = name.trim_end_matches('_');
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        "VALUE".to_owned()
+    } else {
+        name.to_owned()
+    }
+}
+
+/// Returns the job of a workflow step, unless it is a step of a composite action.
+fn normal_job<'a>(step: &DocumentStep<'a>) -> Option<&'a NormalJob> {
+    match step.job {
+        Some((_job_name, Job::Normal(job))) => Some(job),
+        _ => None,
+    }
+}
+
+/// Returns a fix that moves all expressions in the `run` field of a step into environment
+/// variables of the step.
+fn move_expressions_to_env(
+    document: &Document,
+    document_step: &DocumentStep,
+    run: &Spanned<String>,
+) -> Option<Fix> {
+    let shell = Shell::of_step(document, document_step)?;
+    let step = document_step.step;
+    let step_node = document_step.node(document)?;
+    let job = normal_job(document_step);
+    let is_taken = |name: &str| {
+        RESERVED_ENV_NAMES.contains(&name)
+            || name.starts_with("GITHUB_")
+            || name.starts_with("RUNNER_")
+            || [
+                document
+                    .as_workflow()
+                    .and_then(|workflow| workflow.env.as_ref()),
+                job.and_then(|job| job.env.as_ref()),
+                step.env.as_ref(),
+            ]
+            .into_iter()
+            .flatten()
+            .any(|env| env.contains_key(name))
+    };
+
+    // Replace the expressions with references to environment variables.
+    let mut variables: Vec<(String, &str)> = Vec::new();
+    let mut edits = Vec::new();
+    let mut quoting = Quoting::Unquoted;
+    let mut offset = 0;
+    for token in tokenize(run) {
+        let start = offset;
+        offset += token.value().len();
+        if token.kind() == TokenKind::String {
+            quoting = shell.quoting_after(quoting, token.value());
+            offset += "${{".len();
+            continue;
+        }
+
+        // The range of the expression including `${{` and `}}`.
+        offset += "}}".len();
+        let range = start - "${{".len()..offset;
+        let text = run.get(range.clone()).filter(|text| text.ends_with("}}"))?;
+        let span = document.source().locate(run, range);
+        if document.source().text(&span)? != text {
+            return None;
+        }
+
+        let existing_name = variables
+            .iter()
+            .find(|(_, expression)| *expression == text)
+            .map(|(name, _)| name.clone())
+            .or_else(|| {
+                step.env.as_ref().and_then(|env| {
+                    env.iter()
+                        .find(|(_, value)| value.as_str() == text)
+                        .map(|(name, _)| name.clone())
+                })
+            });
+        let name = match existing_name {
+            Some(name) => name,
+            None => {
+                let base = env_name(&text["${{".len()..text.len() - "}}".len()]);
+                let name = (1..)
+                    .map(|n| match n {
+                        1 => base.clone(),
+                        n => format!("{base}_{n}"),
+                    })
+                    .find(|name| {
+                        !is_taken(name) && !variables.iter().any(|(other, _)| other == name)
+                    })?;
+                variables.push((name.clone(), text));
+                name
+            }
+        };
+        edits.push(Edit::replace(span, shell.variable(&name, quoting)?));
+    }
+
+    // Add the environment variables to the step.
+    let entries = variables.iter().map(|(name, expression)| {
+        if expression.contains(": ") || expression.contains(" #") {
+            format!("{name}: '{}'", expression.replace('\'', "''"))
+        } else {
+            format!("{name}: {expression}")
+        }
+    });
+    if let Some(env) = step_node.get_node("env") {
+        let first_key = env.as_mapping()?.keys().next()?.span().start()?;
+        let indent = " ".repeat(document.source().indentation(first_key)?);
+        let text: String = entries.map(|entry| format!("{entry}\n{indent}")).collect();
+        edits.push(Edit::insert(*first_key, text));
+    } else if !variables.is_empty() {
+        let run_key = key_start(step_node, "run")?;
+        let indent = " ".repeat(document.source().indentation(run_key)?);
+        let mut text = "env:\n".to_owned();
+        entries.for_each(|entry| text.push_str(&format!("{indent}  {entry}\n")));
+        text.push_str(&indent);
+        edits.push(Edit::insert(*run_key, text));
+    }
+
+    Some(Fix::new(
+        "Pass expressions to the script via environment variables",
+        edits,
+    ))
+}
+
+#[policy(
+    severity = "low",
+    confidence = "low",
+    category = "injection",
+    tags = ["expressions"],
+    enabled = false
+)]
 /// No step should be using a GitHub Actions Expression in the `run` field.
 ///
 /// Instead, the expression should be assigned to an environment variable which is used in the
 /// script. The reason for this is that the result of the expression is substituted as-is, which
 /// can lead to quoting issues and might be exploitable by an attacker (i.e., script injection).
 ///
+/// Steps of composite actions are checked as well.
+///
 /// # Examples
 ///
 /// ## Not OK: Job uses Expression in `run` field
@@ -40,9 +480,24 @@ use ghastly_macros::policy;
 ///       - run: echo "${{ github.event.pull_request.title }}"
 /// ```
 ///
+/// ## Not OK: Composite Action uses Expression in `run` field
+///
+/// ```yaml
+/// name: Greet
+/// inputs:
+///   who-to-greet:
+///     required: true
+/// runs:
+///   using: composite
+///   steps:
+///     - run: echo "Hello ${{ inputs.who-to-greet }}"
+///       shell: bash
+/// ```
+///
 /// ## OK: Job uses Expression via `env` field
 ///
-/// Jobs that have `write-all` token permission violates this policy.
+/// The expression is evaluated into an environment variable, which the shell expands as data
+/// instead of interpreting it as part of the script.
 ///
 /// ```yaml
 /// on: [pull_request]
@@ -59,20 +514,350 @@ use ghastly_macros::policy;
 ///
 /// - <https://docs.github.com/de/actions/security-for-github-actions/security-guides/security-hardening-for-github-actions#understanding-the-risk-of-script-injections>
 /// - <https://docs.github.com/de/actions/security-for-github-actions/security-guides/security-hardening-for-github-actions#good-practices-for-mitigating-script-injection-attacks>
-pub fn no_github_expr_in_run(workflow: &Workflow) -> Vec<PolicyViolation> {
-    workflow
-        .jobs
+///
+/// # See also
+///
+/// This is a strict variant of `no_untrusted_expr_in_run`, which only reports expressions that
+/// can be controlled by an attacker. Violations of this policy have low severity.
+///
+/// This policy is disabled by default. To enable it, add the following to the configuration file
+/// (`.ghastly.toml`):
+///
+/// ```toml
+/// [policies.no_github_expr_in_run]
+/// enabled = true
+/// ```
+///
+/// # Fix
+///
+/// For `bash`, `sh` and `pwsh` steps, `ghastly fix` moves the expressions into environment
+/// variables of the step and references them in the script instead (e.g.
+/// `"${PULL_REQUEST_TITLE}"`).
+pub fn no_github_expr_in_run(document: &Document) -> Vec<PolicyViolation> {
+    document
+        .steps()
         .iter()
-        .flat_map(|(job_name, job)| {
-            job.steps.iter().flat_map(move |steps| steps.iter().enumerate().filter_map(move |(step_index, step)| step.run.as_ref().and_then(|run| {
-                if tokenize(run).any(|token| token.kind() == TokenKind::Expression) {
-                    Some(PolicyViolation::new(
-                        run.span().to_owned(),
-                        format!("Step {} of job {} should not directly include GitHub expression in the 'run' field.", step_index + 1, job_name),
-                    ))
-                } else {
-                    None
+        .filter_map(|step| {
+            let run = step.step.run.as_ref()?;
+            // Point at the first expression, even if it is malformed.
+            let expr = parse_template(run).next()?;
+            let range = expr.map_or_else(|error| error.span(), |expr| expr.span);
+            Some(
+                PolicyViolation::new(
+                    document.source().locate(run, range),
+                    format!(
+                        "{} should not directly include GitHub expression in the 'run' field.",
+                        step.description()
+                    ),
+                )
+                .with_document_step(step)
+                .with_key("run")
+                .with_fix(move_expressions_to_env(document, step, run)),
+            )
+        })
+        .collect()
+}
+
+#[policy(severity = "high", confidence = "medium", category = "injection", tags = ["expressions"])]
+/// No step should use attacker-controllable contexts in the `run` field.
+///
+/// The result of an expression is substituted into the script as-is. If the expression refers
+/// to data that can be controlled by an attacker (e.g. the title of a pull request or the body of
+/// an issue comment), the attacker can inject arbitrary commands into the script. Such values
+/// should be passed via environment variables instead.
+///
+/// Steps of composite actions are checked as well, and so are the `args` and entrypoints of Docker
+/// actions, which are substituted into the command of the container. Inputs of actions are
+/// reported as potentially attacker-controlled, because they are often set to untrusted values by
+/// the calling workflow.
+///
+/// Inputs of reusable workflows are followed to the jobs in the repository that call the
+/// workflow. If a calling job passes an attacker-controlled context to the input (e.g. in a
+/// workflow triggered by `pull_request_target`), the input is reported as attacker-controlled.
+///
+/// Contexts that are known to be attacker-controlled (e.g. `github.event.pull_request.title`,
+/// `github.head_ref` or `github.event.comment.body`) are reported with high severity. Contexts
+/// that may contain attacker-controlled data, such as `inputs.*` or whole objects like
+/// `toJSON(github.event)`, are reported with medium severity.
+///
+/// # Examples
+///
+/// ## Not OK: Job uses Pull Request Title in `run` field
+///
+/// ```yaml
+/// on: [pull_request]
+/// jobs:
+///   job-with-untrusted-expression-in-run:
+///     runs-on: ubuntu-latest
+///     steps:
+///       - run: echo "${{ github.event.pull_request.title }}"
+/// ```
+///
+/// ## Not OK: Job uses Commit Messages in `run` field
+///
+/// ```yaml
+/// on: [push]
+/// jobs:
+///   job-with-untrusted-expression-in-run:
+///     runs-on: ubuntu-latest
+///     steps:
+///       - run: echo "${{ join(github.event.commits.*.message, ', ') }}"
+/// ```
+///
+/// ## Not OK: Composite Action uses Input in `run` field
+///
+/// ```yaml
+/// name: Comment
+/// inputs:
+///   body:
+///     required: true
+/// runs:
+///   using: composite
+///   steps:
+///     - run: gh pr comment --body "${{ inputs.body }}"
+///       shell: bash
+/// ```
+///
+/// ## Not OK: Docker Action passes Pull Request Title in `args`
+///
+/// ```yaml
+/// name: Label
+/// runs:
+///   using: docker
+///   image: Dockerfile
+///   entrypoint: /bin/sh
+///   args:
+///     - -c
+///     - ./label.sh "${{ github.event.pull_request.title }}"
+/// ```
+///
+/// ## Not OK: Reusable Workflow uses Input set to Pull Request Title in `run` field
+///
+/// ```yaml
+/// # file: .github/workflows/greet.yml
+/// on:
+///   workflow_call:
+///     inputs:
+///       title:
+///         type: string
+/// jobs:
+///   greet:
+///     runs-on: ubuntu-latest
+///     steps:
+///       - run: echo "${{ inputs.title }}"
+/// ```
+///
+/// ```yaml
+/// # file: .github/workflows/pull-request.yml
+/// on: [pull_request_target]
+/// jobs:
+///   greet:
+///     uses: ./.github/workflows/greet.yml
+///     with:
+///       title: ${{ github.event.pull_request.title }}
+/// ```
+///
+/// ## OK: Job uses Pull Request Title via `env` field
+///
+/// ```yaml
+/// on: [pull_request]
+/// jobs:
+///   job-with-expression-in-env:
+///     runs-on: ubuntu-latest
+///     steps:
+///       - run: echo "${PULL_REQUEST_TITLE}"
+///         env:
+///           PULL_REQUEST_TITLE: ${{ github.event.pull_request.title }}
+/// ```
+///
+/// ## OK: Job uses Trusted Context in `run` field
+///
+/// ```yaml
+/// on: [pull_request]
+/// jobs:
+///   job-with-trusted-expression-in-run:
+///     runs-on: ${{ matrix.os }}
+///     strategy:
+///       matrix:
+///         os: [ubuntu-latest, windows-latest]
+///     steps:
+///       - run: echo "Running ${{ github.sha }} on ${{ matrix.os }}"
+/// ```
+///
+/// # References
+///
+/// - <https://securitylab.github.com/resources/github-actions-untrusted-input/>
+/// - <https://docs.github.com/de/actions/security-for-github-actions/security-guides/security-hardening-for-github-actions#understanding-the-risk-of-script-injections>
+///
+/// # Fix
+///
+/// For `bash`, `sh` and `pwsh` steps, `ghastly fix` moves the expressions of the script into
+/// environment variables of the step, like for `no_github_expr_in_run`.
+pub fn no_untrusted_expr_in_run(
+    document: &Document,
+    context: &PolicyContext,
+) -> Vec<PolicyViolation> {
+    let steps = document.steps().into_iter().flat_map(|step| {
+        step.step.run.iter().flat_map(move |run| {
+            // The fix moves all expressions of the script at once, so it is only attached to the
+            // first violation.
+            untrusted_expr_violations(document, context, run, "run", &step.description())
+                .into_iter()
+                .enumerate()
+                .map(move |(index, violation)| {
+                    let fix = (index == 0)
+                        .then(|| move_expressions_to_env(document, &step, run))
+                        .flatten();
+                    violation.with_document_step(&step).with_fix(fix)
+                })
+        })
+    });
+    let docker = document
+        .as_action()
+        .and_then(|action| match &*action.runs {
+            Runs::Docker(runs) => Some(runs.commands()),
+            Runs::Composite(_) | Runs::JavaScript(_) => None,
+        })
+        .into_iter()
+        .flatten()
+        .flat_map(|(field, value)| {
+            untrusted_expr_violations(document, context, value, field, "The Docker action")
+        });
+    steps.chain(docker).collect()
+}
+
+/// Reports the attacker-controllable contexts in the expressions of a field whose value is
+/// substituted into a command.
+fn untrusted_expr_violations(
+    document: &Document,
+    context: &PolicyContext,
+    value: &Spanned<String>,
+    field: &str,
+    subject: &str,
+) -> Vec<PolicyViolation> {
+    parse_template(value)
+        .filter_map(Result::ok)
+        .flat_map(|expr| {
+            tainted_contexts(&expr)
+                .into_iter()
+                .map(|(taint, path, context)| (taint, path, context.span.clone()))
+                .collect::<Vec<_>>()
+        })
+        .map(|(taint, path, range)| {
+            let source = match taint {
+                Taint::Untrusted => None,
+                Taint::Suspicious => {
+                    untrusted_input_source(context.repository(), context.path(), &path, 0)
                 }
-            })))
-        }).collect()
+            };
+            let (severity, description) = match (taint, &source) {
+                (Taint::Untrusted, _) | (_, Some(_)) => (Severity::High, "attacker-controlled"),
+                (Taint::Suspicious, None) => (Severity::Medium, "potentially attacker-controlled"),
+            };
+            let origin = source
+                .map(|(source, call)| {
+                    format!(
+                        " (set to '{}' by job {} of '{}')",
+                        source,
+                        call.job_name,
+                        context.repository().relative_path(call.path).display()
+                    )
+                })
+                .unwrap_or_default();
+            PolicyViolation::new(
+                document.source().locate(value, range),
+                format!(
+                    "{} uses {} context '{}' in the '{}' field{}.",
+                    subject, description, path, field, origin
+                ),
+            )
+            .with_severity(severity)
+            .with_key(format!("{field}: {path}"))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_classify_context() {
+        assert_eq!(
+            classify_context("github.event.pull_request.title"),
+            Some(Taint::Untrusted)
+        );
+        assert_eq!(
+            classify_context("github.event.commits[0].message"),
+            Some(Taint::Untrusted)
+        );
+        assert_eq!(classify_context("inputs.name"), Some(Taint::Suspicious));
+        assert_eq!(
+            classify_context("github.event.pull_request"),
+            Some(Taint::Suspicious)
+        );
+        assert_eq!(classify_context("github.event"), Some(Taint::Suspicious));
+        assert_eq!(classify_context("github.sha"), None);
+        assert_eq!(classify_context("matrix.os"), None);
+        assert_eq!(classify_context("github.event.pull_request.head.sha"), None);
+    }
+
+    #[test]
+    fn test_untrusted_input_of_reusable_workflow() {
+        let document = |source: &str| {
+            Document::from_reader(&mut source.as_bytes(), crate::FileKind::Workflow).unwrap()
+        };
+        let callee = "on:\n  workflow_call:\n    inputs:\n      title:\n        type: string\njobs:\n  greet:\n    runs-on: ubuntu-latest\n    steps:\n      - run: echo \"${{ inputs.title }}\"\n";
+        let caller = "on:\n  workflow_call:\n    inputs:\n      title:\n        type: string\njobs:\n  call:\n    uses: ./.github/workflows/greet.yml\n    with:\n      title: ${{ inputs.title }}\n";
+        let top_level_caller = "on: [pull_request_target]\njobs:\n  call:\n    uses: ./.github/workflows/call.yml\n    with:\n      title: ${{ github.event.pull_request.title }}\n";
+        let mut repository = Repository::new("/repo");
+        repository.insert("/repo/.github/workflows/greet.yml", document(callee));
+        repository.insert("/repo/.github/workflows/call.yml", document(caller));
+        let options = Default::default();
+        let check = |repository: &Repository| {
+            let context = PolicyContext::new(
+                Path::new("/repo/.github/workflows/greet.yml"),
+                &options,
+                repository,
+            );
+            no_untrusted_expr_in_run(&document(callee), &context)
+        };
+
+        // The input is only passed on by the calling workflow.
+        let violations = check(&repository);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].severity(), Some(Severity::Medium));
+
+        repository.insert("/repo/.github/workflows/pr.yml", document(top_level_caller));
+        let violations = check(&repository);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].severity(), Some(Severity::High));
+        assert_eq!(
+            violations[0].message(),
+            "Step 1 of job greet uses attacker-controlled context 'inputs.title' in the 'run' field (set to 'github.event.pull_request.title' by job call of '.github/workflows/pr.yml')."
+        );
+    }
+
+    #[test]
+    fn test_env_name() {
+        assert_eq!(
+            env_name(" github.event.pull_request.title "),
+            "PULL_REQUEST_TITLE"
+        );
+        assert_eq!(env_name("github.head_ref"), "HEAD_REF");
+        assert_eq!(
+            env_name("steps.build.outputs.image-tag"),
+            "STEPS_BUILD_OUTPUTS_IMAGE_TAG"
+        );
+        assert_eq!(env_name("format('{0}', inputs.name)"), "VALUE");
+    }
+
+    #[test]
+    fn test_quoting_after() {
+        let quoting = |text| Shell::Posix.quoting_after(Quoting::Unquoted, text);
+        assert_eq!(quoting("echo "), Quoting::Unquoted);
+        assert_eq!(quoting("echo \"a "), Quoting::Double);
+        assert_eq!(quoting("echo 'a \" "), Quoting::Single);
+        assert_eq!(quoting("echo \\' # don't\necho "), Quoting::Unquoted);
+    }
 }
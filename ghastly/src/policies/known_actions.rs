@@ -0,0 +1,70 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::parser::action_ref::ActionRef;
+
+/// Facts about a well-known action that policies rely on.
+#[derive(Debug)]
+pub struct KnownAction {
+    /// The action, as pattern accepted by [`ActionRef::matches`] (e.g. `actions/checkout`).
+    pub name: &'static str,
+    /// Inputs that select the code that the action checks out into the workspace (e.g. `ref`).
+    ///
+    /// Empty if the action does not check out code.
+    pub checkout_inputs: &'static [&'static str],
+    /// Whether the action executes code from the workspace, e.g. build scripts or hooks that are
+    /// configured in the repository.
+    pub executes_workspace_code: bool,
+}
+
+/// The database of well-known actions.
+const KNOWN_ACTIONS: &[KnownAction] = &[
+    KnownAction {
+        name: "actions/checkout",
+        checkout_inputs: &["ref", "repository"],
+        executes_workspace_code: false,
+    },
+    KnownAction {
+        name: "cypress-io/github-action",
+        checkout_inputs: &[],
+        executes_workspace_code: true,
+    },
+    KnownAction {
+        name: "goreleaser/goreleaser-action",
+        checkout_inputs: &[],
+        executes_workspace_code: true,
+    },
+    KnownAction {
+        name: "pre-commit/action",
+        checkout_inputs: &[],
+        executes_workspace_code: true,
+    },
+];
+
+/// Returns the facts about the action, if it is a well-known action.
+pub fn find_known_action(uses: &ActionRef) -> Option<&'static KnownAction> {
+    KNOWN_ACTIONS
+        .iter()
+        .find(|known_action| uses.matches(known_action.name))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_find_known_action() {
+        let uses: ActionRef = "actions/checkout@v4".parse().unwrap();
+        assert_eq!(
+            find_known_action(&uses).map(|known_action| known_action.checkout_inputs),
+            Some(&["ref", "repository"][..])
+        );
+        let uses: ActionRef = "actions/setup-node@v4".parse().unwrap();
+        assert!(find_known_action(&uses).is_none());
+    }
+}
@@ -6,39 +6,295 @@
 //
 // SPDX-License-Identifier: MPL-2.0
 
-use crate::parser::workflow::Workflow;
+use crate::fix::Fix;
+use crate::parser::document::{Document, DocumentStep};
 use marked_yaml::Span;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::fmt;
+use std::str::FromStr;
 
+mod actions;
+mod context;
 mod expressions;
+mod known_actions;
 mod permissions;
+mod schema;
+mod suppressions;
+mod triggers;
 
-pub type PolicyCheckFn = fn(workflow: &Workflow) -> Vec<PolicyViolation>;
+pub use context::PolicyContext;
+pub use known_actions::KnownAction;
+pub use schema::{parse_error_violations, WORKFLOW_SCHEMA};
+pub use suppressions::{unused_suppression, UNUSED_SUPPRESSION};
+
+pub type PolicyCheckFn = fn(document: &Document, context: &PolicyContext) -> Vec<PolicyViolation>;
+pub type PolicyOptionsValidateFn = fn(options: &PolicyOptions) -> Result<(), String>;
+
+/// Options of a policy, as set in the configuration file.
+#[derive(Debug, Clone, Default)]
+pub struct PolicyOptions(toml::Table);
+
+impl PolicyOptions {
+    pub const fn new(options: toml::Table) -> Self {
+        Self(options)
+    }
+
+    /// Deserializes the options into the given type.
+    pub fn parse<T>(&self) -> Result<T, String>
+    where
+        T: DeserializeOwned,
+    {
+        toml::Value::Table(self.0.clone())
+            .try_into()
+            .map_err(|err: toml::de::Error| err.message().to_owned())
+    }
+
+    /// Deserializes the options into the given type, falling back to the default if the options
+    /// are invalid.
+    ///
+    /// Options are validated when the configuration is loaded, so this only falls back to the
+    /// default if a policy is checked with options that were not validated.
+    pub fn get<T>(&self) -> T
+    where
+        T: DeserializeOwned + Default,
+    {
+        self.parse().unwrap_or_default()
+    }
+}
+
+/// Returns an error if the options cannot be deserialized into the given type.
+pub fn validate_options<T>(options: &PolicyOptions) -> Result<(), String>
+where
+    T: DeserializeOwned,
+{
+    options.parse::<T>().map(|_| ())
+}
+
+/// The options of policies that do not have any options.
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct NoOptions {}
+
+/// The severity of a policy violation.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Informational finding that does not necessarily indicate a problem.
+    Info,
+    /// Low severity.
+    Low,
+    /// Medium severity.
+    #[default]
+    Medium,
+    /// High severity.
+    High,
+}
+
+impl Severity {
+    /// All severities, from lowest to highest.
+    pub const ALL: [Severity; 4] = [
+        Severity::Info,
+        Severity::Low,
+        Severity::Medium,
+        Severity::High,
+    ];
+
+    /// The name of the severity.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+            Severity::High => "high",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Severity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Severity::ALL
+            .into_iter()
+            .find(|severity| severity.as_str() == s)
+            .ok_or_else(|| format!("unknown severity {:?}", s))
+    }
+}
+
+/// How likely it is that a violation of a policy is a real problem (and not a false positive).
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Confidence {
+    /// Violations often need to be reviewed manually.
+    Low,
+    /// Violations are usually real problems.
+    #[default]
+    Medium,
+    /// Violations are almost always real problems.
+    High,
+}
+
+impl Confidence {
+    /// All confidence levels, from lowest to highest.
+    pub const ALL: [Confidence; 3] = [Confidence::Low, Confidence::Medium, Confidence::High];
+
+    /// The name of the confidence level.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Confidence::Low => "low",
+            Confidence::Medium => "medium",
+            Confidence::High => "high",
+        }
+    }
+}
+
+impl fmt::Display for Confidence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Confidence {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Confidence::ALL
+            .into_iter()
+            .find(|confidence| confidence.as_str() == s)
+            .ok_or_else(|| format!("unknown confidence {:?}", s))
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct Policy {
     pub name: &'static str,
-    check_fn: PolicyCheckFn,
+    /// The function that checks a document, or `None` if the violations of this policy are
+    /// reported by the engine itself (e.g. because they depend on the results of other policies).
+    check_fn: Option<PolicyCheckFn>,
+    validate_options_fn: PolicyOptionsValidateFn,
     pub doc: Option<&'static str>,
+    /// The default severity of violations of this policy.
+    pub severity: Severity,
+    /// How likely it is that a violation is a real problem.
+    pub confidence: Confidence,
+    /// The category of problems that this policy detects (e.g. `injection`).
+    pub category: Option<&'static str>,
+    /// Additional tags.
+    pub tags: &'static [&'static str],
+    /// Whether the policy is checked unless it is enabled or disabled in the configuration.
+    pub enabled: bool,
 }
 
 impl Policy {
     pub const fn new(name: &'static str, check_fn: PolicyCheckFn) -> Self {
+        Self::with_check_fn(name, Some(check_fn))
+    }
+
+    /// Creates a policy whose violations are reported by the engine instead of a check function.
+    pub const fn reported_by_engine(name: &'static str) -> Self {
+        Self::with_check_fn(name, None)
+    }
+
+    const fn with_check_fn(name: &'static str, check_fn: Option<PolicyCheckFn>) -> Self {
         Self {
             name,
             check_fn,
+            validate_options_fn: validate_options::<NoOptions>,
             doc: None,
+            severity: Severity::Medium,
+            confidence: Confidence::Medium,
+            category: None,
+            tags: &[],
+            enabled: true,
         }
     }
 
+    /// Sets the default severity of violations of this policy.
+    pub const fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    /// Sets the confidence of this policy.
+    pub const fn with_confidence(mut self, confidence: Confidence) -> Self {
+        self.confidence = confidence;
+        self
+    }
+
+    /// Sets the category of this policy.
+    pub const fn with_category(mut self, category: &'static str) -> Self {
+        self.category = Some(category);
+        self
+    }
+
+    /// Sets the tags of this policy.
+    pub const fn with_tags(mut self, tags: &'static [&'static str]) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    /// Sets whether this policy is checked unless it is enabled or disabled in the configuration.
+    pub const fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// Sets the type that the options of this policy are deserialized into.
+    pub const fn with_options<T>(mut self) -> Self
+    where
+        T: DeserializeOwned,
+    {
+        self.validate_options_fn = validate_options::<T>;
+        self
+    }
+
     pub const fn with_doc(mut self, doc: &'static str) -> Self {
         self.doc = Some(doc);
         self
     }
 
+    /// The first paragraph of the documentation (if any) as a single line.
+    pub fn summary(&self) -> Option<String> {
+        let doc = self.doc?;
+        let summary = doc
+            .trim_start()
+            .lines()
+            .take_while(|line| !line.trim().is_empty())
+            .map(str::trim)
+            .collect::<Vec<_>>()
+            .join(" ");
+        (!summary.is_empty()).then_some(summary)
+    }
+
+    /// Returns `true` if the violations of this policy are reported by the engine instead of a
+    /// check function.
     #[inline]
-    pub fn check<'a>(&'a self, workflow: &Workflow) -> PolicyCheckOutput<'a> {
-        let violations = (self.check_fn)(workflow);
-        PolicyCheckOutput::new(self, violations)
+    pub const fn is_reported_by_engine(&self) -> bool {
+        self.check_fn.is_none()
+    }
+
+    /// Checks the document, or returns `None` if the policy is reported by the engine.
+    #[inline]
+    pub fn check<'a>(
+        &'a self,
+        document: &Document,
+        context: &PolicyContext,
+    ) -> Option<PolicyCheckOutput<'a>> {
+        let violations = (self.check_fn?)(document, context);
+        Some(PolicyCheckOutput::new(self, violations))
+    }
+
+    /// Returns an error if the options are not valid for this policy.
+    #[inline]
+    pub fn validate_options(&self, options: &PolicyOptions) -> Result<(), String> {
+        (self.validate_options_fn)(options)
     }
 }
 
@@ -46,11 +302,64 @@ impl Policy {
 pub struct PolicyViolation {
     source: Span,
     message: String,
+    severity: Option<Severity>,
+    job: Option<String>,
+    step: Option<String>,
+    key: Option<String>,
+    fix: Option<Fix>,
 }
 
 impl PolicyViolation {
     pub const fn new(source: Span, message: String) -> Self {
-        Self { source, message }
+        Self {
+            source,
+            message,
+            severity: None,
+            job: None,
+            step: None,
+            key: None,
+            fix: None,
+        }
+    }
+
+    /// Sets the ID of the job that this violation belongs to.
+    pub fn with_job(mut self, job: impl Into<String>) -> Self {
+        self.job = Some(job.into());
+        self
+    }
+
+    /// Sets the step that this violation belongs to (see [`Step::key`](crate::parser::workflow::Step::key)).
+    pub fn with_step(mut self, step: impl Into<String>) -> Self {
+        self.step = Some(step.into());
+        self
+    }
+
+    /// Sets the job (if any) and the step that this violation belongs to.
+    pub fn with_document_step(self, step: &DocumentStep) -> Self {
+        let violation = match step.job_name() {
+            Some(job_name) => self.with_job(job_name),
+            None => self,
+        };
+        violation.with_step(step.key())
+    }
+
+    /// Sets the key that distinguishes this violation from other violations of the policy in the
+    /// same job and step (see [`PolicyViolation::key`]).
+    pub fn with_key(mut self, key: impl Into<String>) -> Self {
+        self.key = Some(key.into());
+        self
+    }
+
+    /// Sets the severity of this violation.
+    pub const fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = Some(severity);
+        self
+    }
+
+    /// Sets the fix that resolves this violation (see `ghastly fix`).
+    pub fn with_fix(mut self, fix: Option<Fix>) -> Self {
+        self.fix = fix;
+        self
     }
 
     #[inline]
@@ -62,12 +371,47 @@ impl PolicyViolation {
     pub fn message(&self) -> &str {
         &self.message
     }
+
+    /// The severity of this violation, if set explicitly.
+    #[inline]
+    pub const fn severity(&self) -> Option<Severity> {
+        self.severity
+    }
+
+    /// The ID of the job that this violation belongs to (if any).
+    #[inline]
+    pub fn job(&self) -> Option<&str> {
+        self.job.as_deref()
+    }
+
+    /// The step that this violation belongs to (if any).
+    #[inline]
+    pub fn step(&self) -> Option<&str> {
+        self.step.as_deref()
+    }
+
+    /// The key that distinguishes this violation from other violations of the policy in the same
+    /// job and step, which is part of its fingerprint.
+    ///
+    /// Unless set explicitly, this is the message. Violations whose message contains the position
+    /// of a step (e.g. "Step 2 of job build") set a key that does not.
+    #[inline]
+    pub fn key(&self) -> &str {
+        self.key.as_deref().unwrap_or(&self.message)
+    }
+
+    /// The fix that resolves this violation (if any).
+    #[inline]
+    pub fn fix(&self) -> Option<&Fix> {
+        self.fix.as_ref()
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct PolicyCheckOutput<'a> {
     policy: &'a Policy,
     violations: Vec<PolicyViolation>,
+    severity: Option<Severity>,
 }
 
 impl<'a> PolicyCheckOutput<'a> {
@@ -75,11 +419,21 @@ impl<'a> PolicyCheckOutput<'a> {
         policy: &'a Policy,
         violations: Vec<PolicyViolation>,
     ) -> PolicyCheckOutput<'a> {
-        Self { policy, violations }
+        Self {
+            policy,
+            violations,
+            severity: None,
+        }
+    }
+
+    /// Overrides the severity of all violations (e.g., as configured by the user).
+    pub const fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = Some(severity);
+        self
     }
 
     #[inline]
-    pub const fn policy(&self) -> &Policy {
+    pub const fn policy(&self) -> &'a Policy {
         self.policy
     }
 
@@ -88,10 +442,27 @@ impl<'a> PolicyCheckOutput<'a> {
         &self.violations
     }
 
+    /// Removes all violations for which the predicate returns `false`.
+    #[inline]
+    pub fn retain_violations<F>(&mut self, f: F)
+    where
+        F: FnMut(&PolicyViolation) -> bool,
+    {
+        self.violations.retain(f);
+    }
+
     #[inline]
     pub fn into_violations(self) -> Vec<PolicyViolation> {
         self.violations
     }
+
+    /// The effective severity of the given violation of this policy.
+    #[inline]
+    pub fn severity_of(&self, violation: &PolicyViolation) -> Severity {
+        self.severity
+            .or(violation.severity())
+            .unwrap_or(self.policy.severity)
+    }
 }
 
 inventory::collect!(Policy);
@@ -99,3 +470,23 @@ inventory::collect!(Policy);
 pub fn get_policies() -> impl Iterator<Item = &'static Policy> {
     inventory::iter::<Policy>.into_iter()
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_policy_metadata() {
+        let policy = get_policies()
+            .find(|policy| policy.name == "no_github_expr_in_run")
+            .unwrap();
+        assert_eq!(policy.severity, Severity::Low);
+        assert_eq!(policy.confidence, Confidence::Low);
+        assert_eq!(policy.category, Some("injection"));
+        assert_eq!(policy.tags, ["expressions"]);
+        assert!(!policy.enabled);
+        assert!(policy
+            .doc
+            .is_some_and(|doc| doc.starts_with("No step should be using")));
+    }
+}
@@ -6,12 +6,43 @@
 //
 // SPDX-License-Identifier: MPL-2.0
 
-use super::PolicyViolation;
+use super::{PolicyContext, PolicyViolation};
+use crate::fix::{Edit, Fix};
+use crate::parser::location::key_start;
+use crate::parser::trigger::Event;
 use crate::parser::workflow::{PermissionLevel, Permissions, Workflow};
 use ghastly_macros::policy;
+use marked_yaml::Spanned;
 use std::ops::Deref;
 
-#[policy]
+/// Returns a fix that replaces `read-all` or `write-all` permissions with an empty map.
+fn remove_all_permissions(
+    workflow: &Workflow,
+    permissions: &Spanned<Permissions>,
+    value: &str,
+) -> Option<Fix> {
+    let span = workflow
+        .source()
+        .scalar_span(permissions.span().start()?, value)?;
+    Some(Fix::new(
+        format!("Replace '{value}' with '{{}}'"),
+        vec![Edit::replace(span, "{}")],
+    ))
+}
+
+/// Returns a fix that sets the default permissions of the workflow to an empty map, by inserting
+/// `permissions: {}` in front of the `jobs` key.
+fn insert_workflow_permissions(workflow: &Workflow) -> Option<Fix> {
+    let jobs = key_start(workflow.source().node().as_mapping()?, "jobs")?;
+    (workflow.source().indentation(jobs)? == 0).then(|| {
+        Fix::new(
+            "Set the default permissions of the workflow to '{}'",
+            vec![Edit::insert(*jobs, "permissions: {}\n")],
+        )
+    })
+}
+
+#[policy(severity = "medium", confidence = "high", category = "permissions", tags = ["github-token"])]
 /// No job should use the `read-all` or `write-all` permissions for the `GITHUB_TOKEN`.
 ///
 /// Permissions that are unnecessarily broad violate the principle of least privilege.
@@ -20,10 +51,11 @@ use std::ops::Deref;
 ///
 /// ## Not OK: Jobs with `read-all` token permission
 ///
-/// Jobs that have `read-all` token permission violates this policy.
+/// Jobs that have `read-all` token permission violate this policy.
 ///
 /// ```yaml
 /// name: Job with read-all token permission
+/// on: [push]
 /// jobs:
 ///   foo:
 ///     runs-on: ubuntu-latest
@@ -34,38 +66,40 @@ use std::ops::Deref;
 ///
 /// ## Not OK: Jobs with `write-all` token permission
 ///
-/// Jobs that have `write-all` token permission violates this policy.
+/// Jobs that have `write-all` token permission violate this policy.
 ///
 /// ```yaml
 /// name: Job with write-all token permission
+/// on: [push]
 /// jobs:
 ///   foo:
 ///     runs-on: ubuntu-latest
-///     permissions: read-all
+///     permissions: write-all
 ///     steps:
 ///       - run: echo "Too many permissions"
 /// ```
 ///
-/// ## OK: Jobs without fine-grained token permissions
+/// ## OK: Jobs without token permissions
 ///
-/// Jobs that do not set token permission do not violate this policy.
+/// Jobs that do not set token permissions do not violate this policy.
 ///
 /// ```yaml
-/// name: Job with write-all token permission
+/// name: Job without token permissions
+/// on: [push]
 /// jobs:
 ///   foo:
 ///     runs-on: ubuntu-latest
-///     permissions:
-///         contents: read
 ///     steps:
 ///       - run: echo "This is okay"
 /// ```
+///
 /// ## OK: Jobs with fine-grained token permissions
 ///
-/// Jobs that do not set token permission do not violate this policy.
+/// Jobs that set fine-grained token permissions do not violate this policy.
 ///
 /// ```yaml
-/// name: Job with write-all token permission
+/// name: Job with fine-grained token permissions
+/// on: [push]
 /// jobs:
 ///   foo:
 ///     runs-on: ubuntu-latest
@@ -79,33 +113,50 @@ use std::ops::Deref;
 ///
 /// - <https://docs.github.com/en/actions/writing-workflows/workflow-syntax-for-github-actions#defining-access-for-the-github_token-scopes>
 /// - <https://en.wikipedia.org/wiki/Principle_of_least_privilege>
-/// ```
+///
+/// # Fix
+///
+/// `ghastly fix` replaces `read-all` and `write-all` with `{}` (no permissions). Permissions
+/// that the job actually needs have to be added afterwards.
 pub fn no_all_permissions(workflow: &Workflow) -> Vec<PolicyViolation> {
     workflow
         .jobs
         .iter()
         .filter_map(|(job_name, job)| {
-            job.permissions
-                .as_ref()
+            job.permissions()
                 .and_then(|permissions| match permissions.deref() {
-                    Permissions::ReadAll => Some(PolicyViolation::new(
-                        permissions.span().to_owned(),
-                        format!("Job {} should not use the 'read-all' permission.", job_name),
-                    )),
-                    Permissions::WriteAll => Some(PolicyViolation::new(
-                        permissions.span().to_owned(),
-                        format!(
-                            "Job {} should not use the 'write-all' permission.",
-                            job_name
-                        ),
-                    )),
+                    Permissions::ReadAll => Some(
+                        PolicyViolation::new(
+                            workflow.source().scalar_location(permissions, "read-all"),
+                            format!("Job {} should not use the 'read-all' permission.", job_name),
+                        )
+                        .with_job(job_name)
+                        .with_fix(remove_all_permissions(
+                            workflow,
+                            permissions,
+                            "read-all",
+                        )),
+                    ),
+                    Permissions::WriteAll => {
+                        Some(
+                            PolicyViolation::new(
+                                workflow.source().scalar_location(permissions, "write-all"),
+                                format!(
+                                    "Job {} should not use the 'write-all' permission.",
+                                    job_name
+                                ),
+                            )
+                            .with_job(job_name)
+                            .with_fix(remove_all_permissions(workflow, permissions, "write-all")),
+                        )
+                    }
                     _ => None,
                 })
         })
         .collect()
 }
 
-#[policy]
+#[policy(severity = "medium", confidence = "high", category = "permissions", tags = ["github-token"])]
 /// Every job should set individual permissions for the `GITHUB_TOKEN`.
 ///
 /// Permissions that are unnecessarily broad violate the principle of least privilege. Hence, every
@@ -116,6 +167,9 @@ pub fn no_all_permissions(workflow: &Workflow) -> Vec<PolicyViolation> {
 ///
 /// 1. If the workflow sets the default permissions to `none`
 /// 2. If the workflow sets the permissions field and the there is only one job in the workflow.
+/// 3. If the workflow is a reusable workflow and all jobs in the repository that call it set
+///    permissions (on the job or workflow level), because a called workflow cannot have more
+///    permissions than the calling job.
 ///
 /// # Examples
 ///
@@ -139,16 +193,16 @@ pub fn no_all_permissions(workflow: &Workflow) -> Vec<PolicyViolation> {
 /// ```yaml
 /// on: [push]
 /// permissions:
-///     content: write
+///     contents: write
 /// jobs:
-///   job-that-writes-content:
+///   job-that-writes-contents:
 ///     runs-on: ubuntu-latest
 ///     steps:
-///       - run: echo "This job needs 'content: write' permission"
-///   job-that-does-not-write-content:
+///       - run: echo "This job needs write access to the repository contents"
+///   job-that-does-not-write-contents:
 ///     runs-on: ubuntu-latest
 ///     steps:
-///       - run: echo "This job has 'content: write' permission, but doesn't need it"
+///       - run: echo "This job has write access to the repository contents, but does not need it"
 /// ```
 ///
 /// ## OK: Job sets `permissions` Field
@@ -166,6 +220,28 @@ pub fn no_all_permissions(workflow: &Workflow) -> Vec<PolicyViolation> {
 ///       - run: echo "This is okay"
 /// ```
 ///
+/// ## OK: Reusable Workflow called by Job that sets `permissions` Field
+///
+/// ```yaml
+/// # file: .github/workflows/build.yml
+/// on: [workflow_call]
+/// jobs:
+///   build:
+///     runs-on: ubuntu-latest
+///     steps:
+///       - run: make
+/// ```
+///
+/// ```yaml
+/// # file: .github/workflows/ci.yml
+/// on: [push]
+/// jobs:
+///   build:
+///     permissions:
+///       contents: read
+///     uses: ./.github/workflows/build.yml
+/// ```
+///
 /// ## OK: Single-Job Workflow with `permissions` field
 ///
 /// In a workflow with only a single job, the `permission` field may be set on the workflow level
@@ -186,8 +262,12 @@ pub fn no_all_permissions(workflow: &Workflow) -> Vec<PolicyViolation> {
 ///
 /// - <https://docs.github.com/en/actions/writing-workflows/workflow-syntax-for-github-actions#defining-access-for-the-github_token-scopes>
 /// - <https://en.wikipedia.org/wiki/Principle_of_least_privilege>
-/// ```
-pub fn permissions_set(workflow: &Workflow) -> Vec<PolicyViolation> {
+///
+/// # Fix
+///
+/// If the workflow does not set any permissions, `ghastly fix` sets the default permissions of
+/// the workflow to `{}` (no permissions). Jobs that need permissions have to set them afterwards.
+pub fn permissions_set(workflow: &Workflow, context: &PolicyContext) -> Vec<PolicyViolation> {
     // If the workflow sets the default permissions to `none`, the job's `permissions` field may be
     // omitted.
     if workflow.permissions.as_ref().is_some_and(|permissions| {
@@ -208,16 +288,40 @@ pub fn permissions_set(workflow: &Workflow) -> Vec<PolicyViolation> {
         return vec![];
     }
 
-    // Otherwise, every job should specify permissions separately.
+    // If the workflow can only be called by other workflows, the permissions of the calling jobs
+    // apply.
+    let callers = context.repository().callers(context.path());
+    if workflow
+        .on
+        .iter()
+        .all(|trigger| trigger.event() == &Event::WorkflowCall)
+        && !callers.is_empty()
+        && callers.iter().all(|call| call.permissions().is_some())
+    {
+        return vec![];
+    }
+
+    // Otherwise, every job should specify permissions separately. If the workflow does not set
+    // any permissions, this can also be fixed by setting the default permissions to none. Since
+    // this fixes the violations of all jobs at once, the fix is only attached to the first one.
+    let mut fix = workflow
+        .permissions
+        .is_none()
+        .then(|| insert_workflow_permissions(workflow))
+        .flatten();
     workflow
         .jobs
         .iter()
         .filter_map(|(job_name, job)| {
-            if job.permissions.is_none() {
-                Some(PolicyViolation::new(
-                    job.span().to_owned(),
-                    format!("Job '{}' should set 'permissions' field.", job_name),
-                ))
+            if job.permissions().is_none() {
+                Some(
+                    PolicyViolation::new(
+                        job.span().to_owned(),
+                        format!("Job '{}' should set 'permissions' field.", job_name),
+                    )
+                    .with_job(job_name)
+                    .with_fix(fix.take()),
+                )
             } else {
                 None
             }
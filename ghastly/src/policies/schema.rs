@@ -0,0 +1,181 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+use super::PolicyViolation;
+use crate::parser::error::ParseError;
+use crate::parser::schema::{validate_workflow, validate_workflow_source};
+use crate::parser::workflow::Workflow;
+use ghastly_macros::policy;
+
+/// The name of the [`workflow_schema`] policy.
+pub const WORKFLOW_SCHEMA: &str = "workflow_schema";
+
+#[policy(severity = "medium", confidence = "high", category = "configuration")]
+/// Workflows should be valid YAML and follow the workflow syntax.
+///
+/// GitHub does not run workflows that cannot be parsed, e.g. because a required key like
+/// `runs-on` is missing, a key has a value of the wrong type or an action reference lacks a
+/// version. Such workflows are also not checked by the other policies, so problems in them are
+/// reported by this policy instead of aborting the check.
+///
+/// Unknown keys are reported as well. A misspelled key is ignored by GitHub (and the other
+/// policies), so that e.g. a typo in `permissions` silently grants the default permissions.
+/// Invalid permission names and levels, steps that set both `run` and `uses` (or neither) and
+/// unknown events are also reported.
+///
+/// If this policy is disabled, invalid workflows are reported as errors.
+///
+/// # Examples
+///
+/// ## Not OK: Missing `runs-on`
+///
+/// ```yaml
+/// on: [push]
+/// jobs:
+///   build:
+///     steps:
+///       - run: echo "Hello World"
+/// ```
+///
+/// ## Not OK: Action Reference without Version
+///
+/// ```yaml
+/// on: [push]
+/// jobs:
+///   build:
+///     runs-on: ubuntu-latest
+///     steps:
+///       - uses: actions/checkout
+/// ```
+///
+/// ## Not OK: Misspelled Key
+///
+/// ```yaml
+/// on: [push]
+/// permisions: {}
+/// jobs:
+///   build:
+///     runs-on: ubuntu-latest
+///     steps:
+///       - run: echo "Hello World"
+/// ```
+///
+/// ## Not OK: Value of the Wrong Type
+///
+/// ```yaml
+/// on: [push]
+/// jobs:
+///   build:
+///     runs-on: ubuntu-latest
+///     timeout-minutes: one hour
+///     steps:
+///       - run: echo "Hello World"
+/// ```
+///
+/// ## Not OK: Step with `run` and `uses`
+///
+/// ```yaml
+/// on: [push]
+/// jobs:
+///   build:
+///     runs-on: ubuntu-latest
+///     steps:
+///       - uses: actions/checkout@v4
+///         run: echo "Hello World"
+/// ```
+///
+/// ## OK: Valid Workflow
+///
+/// ```yaml
+/// on: [push]
+/// jobs:
+///   build:
+///     runs-on: ubuntu-latest
+///     steps:
+///       - uses: actions/checkout@v4
+/// ```
+///
+/// # References
+///
+/// - [Workflow syntax for GitHub Actions](https://docs.github.com/en/actions/writing-workflows/workflow-syntax-for-github-actions)
+pub fn workflow_schema(workflow: &Workflow) -> Vec<PolicyViolation> {
+    validate_workflow(workflow.source().as_str(), workflow.source().node())
+        .iter()
+        .map(schema_violation)
+        .collect()
+}
+
+/// Returns the violations of a workflow that could not be parsed.
+///
+/// The parser stops at the first error, so the workflow is validated against the workflow syntax
+/// to find all errors. The parse error is only reported if it is unrelated to those.
+pub fn parse_error_violations(source: &str, err: &ParseError) -> Vec<PolicyViolation> {
+    let mut errors = validate_workflow_source(source);
+    let path = err.path().unwrap_or_default();
+    if !errors.iter().any(|other| {
+        let other_path = other.path().unwrap_or_default();
+        is_within(path, other_path) || is_within(other_path, path)
+    }) {
+        errors.push(err.clone());
+    }
+    errors.iter().map(schema_violation).collect()
+}
+
+/// Returns `true` if the path is the same as or a descendant of the other path.
+fn is_within(path: &str, ancestor: &str) -> bool {
+    path.strip_prefix(ancestor).is_some_and(|rest| {
+        rest.is_empty() || (!ancestor.is_empty() && rest.starts_with(['.', '[']))
+    })
+}
+
+/// Returns the violation for an error in the workflow.
+fn schema_violation(err: &ParseError) -> PolicyViolation {
+    let message = match err.path() {
+        Some(path) => format!("Workflow is invalid at '{path}': {}.", err.message()),
+        None => format!("Workflow is invalid: {}.", err.message()),
+    };
+    PolicyViolation::new(*err.span(), message)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_error_violations() {
+        let messages = |source: &str| -> Vec<String> {
+            let err = Workflow::from_reader(&mut source.as_bytes())
+                .map(|_| ())
+                .unwrap_err();
+            let crate::Error::ParseError(err) = err else {
+                panic!("unexpected error {err}");
+            };
+            parse_error_violations(source, &err)
+                .iter()
+                .map(|violation| violation.message().to_owned())
+                .collect()
+        };
+
+        // The missing `runs-on` is reported by the schema validation.
+        assert_eq!(
+            messages("on: push\njobs:\n  build:\n    runs_on: ubuntu-latest\n"),
+            vec![
+                "Workflow is invalid at 'jobs.build': missing key `runs-on` or `uses`.",
+                "Workflow is invalid at 'jobs.build': unknown key `runs_on`, did you mean `runs-on`.",
+            ]
+        );
+        // The missing version is only reported by the parser.
+        assert_eq!(
+            messages("on: push\nfoo: bar\njobs:\n  build:\n    runs-on: ubuntu-latest\n    steps:\n      - uses: actions/checkout\n"),
+            vec![
+                "Workflow is invalid: unknown key `foo`.",
+                "Workflow is invalid at 'jobs.build.steps[0].uses': action reference \"actions/checkout\" is missing a version.",
+            ]
+        );
+    }
+}
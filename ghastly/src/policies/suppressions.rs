@@ -0,0 +1,99 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+use super::{get_policies, PolicyCheckOutput, PolicyViolation};
+use crate::suppression::Suppression;
+use ghastly_macros::policy;
+use std::collections::HashSet;
+
+/// The name of the [`unused_suppression`] policy.
+pub const UNUSED_SUPPRESSION: &str = "unused_suppression";
+
+#[policy(
+    severity = "info",
+    confidence = "high",
+    category = "configuration",
+    reported_by_engine
+)]
+/// Suppression comments should only be used where they suppress a violation.
+///
+/// Violations of a policy can be suppressed with a comment of the form
+/// `# ghastly: ignore[policy_name, ...] reason="..."`. The comment applies to:
+///
+/// - the line it is on, if it follows some content,
+/// - the whole file, if it is on its own line before any content,
+/// - otherwise, the next line with content.
+///
+/// If that line is a key without a value on the same line (e.g., the name of a job), the
+/// suppression also applies to the first line of the value. Suppressions that do not suppress
+/// any violation (e.g., because the workflow has been fixed in the meantime or because the policy
+/// name is misspelled) are reported by this policy, so that they do not hide new violations.
+///
+/// # Examples
+///
+/// ## Not OK: Suppression of a Policy that is not violated
+///
+/// ```yaml
+/// on: [push]
+/// jobs:
+///   build:
+///     runs-on: ubuntu-latest
+///     permissions: # ghastly: ignore[permissions_set] reason="Needs to publish releases"
+///       contents: write
+///     steps:
+///       - run: echo "Hello World"
+/// ```
+///
+/// ## OK: Suppression of a Violation
+///
+/// ```yaml
+/// on: [push]
+/// jobs:
+///   build:
+///     runs-on: ubuntu-latest
+///     # ghastly: ignore[no_all_permissions] reason="Needs to publish releases and packages"
+///     permissions: write-all
+///     steps:
+///       - run: echo "Hello World"
+/// ```
+pub fn unused_suppression(
+    suppressions: &[Suppression],
+    used_suppressions: &HashSet<(usize, &str)>,
+    outputs: &[PolicyCheckOutput],
+) -> Vec<PolicyViolation> {
+    // Unused suppressions can only be detected after all other policies have been checked, so
+    // this is called by the engine instead of being checked like other policies. Suppressions of
+    // policies that were checked (or do not exist), but not violated, are reported.
+    suppressions
+        .iter()
+        .enumerate()
+        .flat_map(|(index, suppression)| {
+            suppression
+                .policies()
+                .map(move |policy_name| (index, suppression, policy_name))
+        })
+        .filter(|(index, _suppression, policy_name)| {
+            !used_suppressions.contains(&(*index, *policy_name))
+        })
+        .filter_map(|(_index, suppression, policy_name)| {
+            let message = if get_policies().any(|policy| policy.name == policy_name) {
+                outputs
+                    .iter()
+                    .any(|output| output.policy().name == policy_name)
+                    .then(|| format!("Suppression of policy '{policy_name}' is unused."))
+            } else {
+                Some(format!("Suppression of unknown policy '{policy_name}'."))
+            }?;
+            let message = match suppression.reason() {
+                Some(reason) => format!("{message} (Reason: {reason})"),
+                None => message,
+            };
+            Some(PolicyViolation::new(suppression.span(), message))
+        })
+        .collect()
+}
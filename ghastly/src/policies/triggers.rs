@@ -0,0 +1,344 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+use super::expressions::context_matches;
+use super::{PolicyContext, PolicyViolation};
+use crate::parser::{
+    action_ref::ActionRef,
+    expression::parse_template,
+    trigger::Event,
+    workflow::{Step, Workflow},
+};
+use crate::{Repository, MAX_CALL_DEPTH};
+use ghastly_macros::policy;
+use std::path::Path;
+
+/// Events that run in the context of the base repository (with access to secrets and a
+/// privileged `GITHUB_TOKEN`), even if they were caused by a pull request from a fork.
+const PRIVILEGED_EVENTS: &[Event] = &[Event::PullRequestTarget, Event::WorkflowRun];
+
+/// Contexts that refer to the head of a pull request (i.e., code that is controlled by the
+/// author of the pull request). `*` matches any segment, e.g. an array index.
+const PULL_REQUEST_HEAD_CONTEXTS: &[&str] = &[
+    "github.head_ref",
+    "github.event.number",
+    "github.event.pull_request.number",
+    "github.event.pull_request.head.ref",
+    "github.event.pull_request.head.sha",
+    "github.event.pull_request.head.repo.full_name",
+    "github.event.pull_request.merge_commit_sha",
+    "github.event.workflow_run.head_branch",
+    "github.event.workflow_run.head_sha",
+    "github.event.workflow_run.head_commit.id",
+    "github.event.workflow_run.head_repository.full_name",
+    "github.event.workflow_run.pull_requests.*.number",
+    "github.event.workflow_run.pull_requests.*.head.sha",
+    "github.event.workflow_run.pull_requests.*.head.ref",
+];
+
+/// Returns the inputs that select the code that is checked out, if the step uses an action that
+/// checks out code (e.g. `actions/checkout`).
+fn checkout_inputs(step: &Step, context: &PolicyContext) -> Option<&'static [&'static str]> {
+    let known_action = context.known_action(step.uses.as_ref()?)?;
+    (!known_action.checkout_inputs.is_empty()).then_some(known_action.checkout_inputs)
+}
+
+/// Returns the privileged event that triggers the workflow at the given path, either directly or
+/// via a workflow in the repository that calls it (together with the path of that workflow).
+fn privileged_event<'a>(
+    workflow: &Workflow,
+    path: &Path,
+    repository: &'a Repository,
+    depth: usize,
+) -> Option<(&'static Event, Option<&'a Path>)> {
+    if let Some(event) = PRIVILEGED_EVENTS
+        .iter()
+        .find(|event| workflow.on.is_triggered_by(event))
+    {
+        return Some((event, None));
+    }
+    if depth >= MAX_CALL_DEPTH {
+        return None;
+    }
+    repository.callers(path).into_iter().find_map(|call| {
+        privileged_event(call.workflow, call.path, repository, depth + 1)
+            .map(|(event, caller_path)| (event, caller_path.or(Some(call.path))))
+    })
+}
+
+/// Returns `true` if the value contains an expression that refers to the pull request head.
+///
+/// Inputs of a reusable workflow refer to the pull request head if a job in the repository that
+/// calls the workflow at the given path sets them to such an expression.
+fn refers_to_pull_request_head(
+    value: &str,
+    path: &Path,
+    repository: &Repository,
+    depth: usize,
+) -> bool {
+    parse_template(value).filter_map(Result::ok).any(|expr| {
+        expr.iter().any(|expr| {
+            expr.context_path().is_some_and(|context_path| {
+                if PULL_REQUEST_HEAD_CONTEXTS
+                    .iter()
+                    .any(|pattern| context_matches(pattern, &context_path))
+                {
+                    return true;
+                }
+                let Some(input) = context_path.strip_prefix("inputs.") else {
+                    return false;
+                };
+                depth < MAX_CALL_DEPTH
+                    && repository.callers(path).into_iter().any(|call| {
+                        call.input(input).is_some_and(|value| {
+                            refers_to_pull_request_head(value, call.path, repository, depth + 1)
+                        })
+                    })
+            })
+        })
+    })
+}
+
+/// Returns `true` if the step executes code from the workspace, i.e. runs a script, a local
+/// action or an action that is known to execute code from the workspace (e.g. a build tool).
+fn executes_workspace_code(step: &Step, context: &PolicyContext) -> bool {
+    step.run.is_some()
+        || step.uses.as_ref().is_some_and(|uses| {
+            matches!(**uses, ActionRef::Local { .. })
+                || context
+                    .known_action(uses)
+                    .is_some_and(|known_action| known_action.executes_workspace_code)
+        })
+}
+
+#[policy(severity = "high", confidence = "medium", category = "untrusted-checkout", tags = ["pull-request-target", "workflow-run"])]
+/// Workflows triggered by `pull_request_target` or `workflow_run` should not check out and run
+/// code from a pull request.
+///
+/// Unlike `pull_request`, these events run in the context of the base repository, i.e., with
+/// access to repository secrets and a `GITHUB_TOKEN` with write permissions, even if the pull
+/// request comes from a fork. If such a workflow checks out the head of the pull request and then
+/// executes scripts or local actions from it (e.g., build scripts, test runners or package
+/// manager hooks), the author of the pull request can run arbitrary code with these privileges.
+/// This is commonly called a "pwn request".
+///
+/// Some well-known actions also execute code from the workspace, e.g. `pre-commit/action` runs
+/// the hooks that are configured in the repository.
+///
+/// Reusable workflows run in the context of the calling workflow. Hence, they are checked as if
+/// they were triggered by the events of the workflows in the repository that call them, and
+/// inputs that the calling jobs set to the pull request head are followed as well.
+///
+/// # Examples
+///
+/// ## Not OK: Building Pull Request Code in a `pull_request_target` Workflow
+///
+/// ```yaml
+/// on: [pull_request_target]
+/// jobs:
+///   build:
+///     runs-on: ubuntu-latest
+///     permissions:
+///       contents: read
+///     steps:
+///       - uses: actions/checkout@v4
+///         with:
+///           ref: ${{ github.event.pull_request.head.sha }}
+///       - run: make test
+/// ```
+///
+/// ## Not OK: Running a Local Action from Pull Request Code in a `workflow_run` Workflow
+///
+/// ```yaml
+/// on:
+///   workflow_run:
+///     workflows: [CI]
+///     types: [completed]
+/// jobs:
+///   report:
+///     runs-on: ubuntu-latest
+///     permissions:
+///       contents: read
+///     steps:
+///       - uses: actions/checkout@v4
+///         with:
+///           ref: ${{ github.event.workflow_run.head_sha }}
+///       - uses: ./.github/actions/report
+/// ```
+///
+/// ## Not OK: Installing Dependencies of a Pull Request in a `workflow_run` Workflow
+///
+/// Package managers run the install scripts of the checked out `package.json`.
+///
+/// ```yaml
+/// on:
+///   workflow_run:
+///     workflows: [CI]
+///     types: [completed]
+/// jobs:
+///   deploy-preview:
+///     runs-on: ubuntu-latest
+///     permissions:
+///       contents: read
+///     steps:
+///       - uses: actions/checkout@v4
+///         with:
+///           ref: refs/pull/${{ github.event.workflow_run.pull_requests[0].number }}/merge
+///       - run: npm install
+/// ```
+///
+/// ## Not OK: Running Pre-Commit Hooks from Pull Request Code
+///
+/// ```yaml
+/// on: [pull_request_target]
+/// jobs:
+///   lint:
+///     runs-on: ubuntu-latest
+///     permissions:
+///       contents: read
+///     steps:
+///       - uses: actions/checkout@v4
+///         with:
+///           ref: ${{ github.event.pull_request.head.sha }}
+///       - uses: pre-commit/action@v3.0.1
+/// ```
+///
+/// ## Not OK: Reusable Workflow called by a `pull_request_target` Workflow
+///
+/// ```yaml
+/// # file: .github/workflows/test.yml
+/// on:
+///   workflow_call:
+///     inputs:
+///       ref:
+///         type: string
+/// jobs:
+///   test:
+///     runs-on: ubuntu-latest
+///     permissions:
+///       contents: read
+///     steps:
+///       - uses: actions/checkout@v4
+///         with:
+///           ref: ${{ inputs.ref }}
+///       - run: make test
+/// ```
+///
+/// ```yaml
+/// # file: .github/workflows/pull-request.yml
+/// on: [pull_request_target]
+/// jobs:
+///   test:
+///     permissions:
+///       contents: read
+///     uses: ./.github/workflows/test.yml
+///     with:
+///       ref: ${{ github.event.pull_request.head.sha }}
+/// ```
+///
+/// ## OK: Checking out the Base Repository
+///
+/// Without the `ref` input, `actions/checkout` checks out the base branch, which is trusted.
+///
+/// ```yaml
+/// on: [pull_request_target]
+/// jobs:
+///   label:
+///     runs-on: ubuntu-latest
+///     permissions:
+///       contents: read
+///     steps:
+///       - uses: actions/checkout@v4
+///       - run: ./scripts/label-pull-request.sh
+/// ```
+///
+/// ## OK: Building Pull Request Code in a `pull_request` Workflow
+///
+/// ```yaml
+/// on: [pull_request]
+/// jobs:
+///   build:
+///     runs-on: ubuntu-latest
+///     permissions:
+///       contents: read
+///     steps:
+///       - uses: actions/checkout@v4
+///         with:
+///           ref: ${{ github.event.pull_request.head.sha }}
+///       - run: make test
+/// ```
+///
+/// # References
+///
+/// - <https://securitylab.github.com/resources/github-actions-preventing-pwn-requests/>
+/// - <https://docs.github.com/en/actions/writing-workflows/choosing-when-your-workflow-runs/events-that-trigger-workflows#pull_request_target>
+pub fn no_pwn_request(workflow: &Workflow, context: &PolicyContext) -> Vec<PolicyViolation> {
+    let repository = context.repository();
+    let Some((event, caller_path)) = privileged_event(workflow, context.path(), repository, 0)
+    else {
+        return vec![];
+    };
+    let trigger = match caller_path {
+        Some(caller_path) => format!(
+            "a workflow called by '{}', which is triggered by '{}'",
+            repository.relative_path(caller_path).display(),
+            event
+        ),
+        None => format!("a workflow triggered by '{}'", event),
+    };
+    let trigger = &trigger;
+
+    workflow
+        .jobs
+        .iter()
+        .flat_map(|(job_name, job)| {
+            job.steps().into_iter().flat_map(move |steps| {
+                steps
+                    .iter()
+                    .enumerate()
+                    .filter_map(move |(checkout_index, checkout)| {
+                        let inputs = checkout_inputs(checkout, context)?;
+                        let untrusted_input = checkout.with.as_ref().and_then(|with| {
+                            inputs
+                                .iter()
+                                .filter_map(|input| with.get(*input))
+                                .find(|value| {
+                                    refers_to_pull_request_head(
+                                        value,
+                                        context.path(),
+                                        repository,
+                                        0,
+                                    )
+                                })
+                        })?;
+                        let execute_index = steps
+                            .iter()
+                            .enumerate()
+                            .skip(checkout_index + 1)
+                            .find(|(_step_index, step)| executes_workspace_code(step, context))
+                            .map(|(step_index, _step)| step_index)?;
+                        Some(
+                            PolicyViolation::new(
+                                workflow.source().scalar_location(untrusted_input, untrusted_input),
+                                format!(
+                                    "Step {} of job {} checks out pull request code that is executed in step {} of {}.",
+                                    checkout_index + 1,
+                                    job_name,
+                                    execute_index + 1,
+                                    trigger
+                                ),
+                            )
+                            .with_job(job_name)
+                            .with_step(checkout.key(checkout_index))
+                            .with_key(trigger.as_str()),
+                        )
+                    })
+            })
+        })
+        .collect()
+}
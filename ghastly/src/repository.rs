@@ -0,0 +1,343 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::config::absolute_path;
+use crate::parser::action::Action;
+use crate::parser::action_ref::ActionRef;
+use crate::parser::document::Document;
+use crate::parser::workflow::{Job, JobSecrets, Permissions, ReusableWorkflowCallJob, Workflow};
+use crate::{discover_files, FileKind};
+use marked_yaml::Spanned;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+/// The names of action metadata files.
+const ACTION_FILE_NAMES: [&str; 2] = ["action.yml", "action.yaml"];
+
+/// The maximum number of nested reusable workflow calls that are followed, which is the limit
+/// of GitHub Actions (and also prevents endless recursion on cyclic calls).
+pub const MAX_CALL_DEPTH: usize = 10;
+
+/// An index of the workflows and actions of a repository.
+///
+/// Policies use the repository to look up other files, e.g. the local actions that are used by a
+/// workflow. All files are loaded before any file is checked.
+#[derive(Debug, Default)]
+pub struct Repository {
+    /// The root directory of the repository, which local actions and workflows are relative to.
+    root: PathBuf,
+    /// The parsed files, by absolute path.
+    documents: BTreeMap<PathBuf, Document>,
+}
+
+impl Repository {
+    /// Creates an empty repository with the given root directory.
+    pub fn new(root: impl AsRef<Path>) -> Self {
+        Self {
+            root: absolute_path(root.as_ref()),
+            documents: BTreeMap::new(),
+        }
+    }
+
+    /// The root directory of the Git repository that contains the given file or directory.
+    ///
+    /// Outside of a Git repository, this is the directory that contains the `.github` directory
+    /// of the path (or the directory itself if there is none).
+    pub fn find_root(path: impl AsRef<Path>) -> PathBuf {
+        let path = absolute_path(path.as_ref());
+        let directory = match path.parent() {
+            Some(parent) if path.is_file() => parent,
+            _ => &path,
+        };
+        directory
+            .ancestors()
+            .find(|ancestor| ancestor.join(".git").exists())
+            .or_else(|| {
+                directory
+                    .ancestors()
+                    .find(|ancestor| ancestor.ends_with(".github"))
+                    .and_then(Path::parent)
+            })
+            .unwrap_or(directory)
+            .to_owned()
+    }
+
+    /// Loads all workflows and action metadata files of the repository that contains the given
+    /// file or directory (see [`Repository::find_root`]).
+    ///
+    /// Files that cannot be read or parsed are skipped, because they are reported when they are
+    /// checked.
+    pub fn discover(path: impl AsRef<Path>) -> Self {
+        let root = Self::find_root(path);
+        let mut repository = Self::new(&root);
+        discover_files(&[&root])
+            .filter_map(Result::ok)
+            .for_each(|(path, kind)| {
+                let _ = repository.load(path, kind);
+            });
+        repository
+    }
+
+    /// Reads and parses the file and adds it to the repository.
+    pub fn load(&mut self, path: impl AsRef<Path>, kind: FileKind) -> crate::Result<()> {
+        let path = path.as_ref();
+        let document = Document::from_reader(&mut File::open(path)?, kind)?;
+        self.insert(path, document);
+        Ok(())
+    }
+
+    /// Adds the document to the repository, replacing the document at the same path (if any).
+    pub fn insert(&mut self, path: impl AsRef<Path>, document: Document) {
+        self.documents
+            .insert(absolute_path(path.as_ref()), document);
+    }
+
+    /// The root directory of the repository.
+    #[inline]
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The document at the given path, if it is part of the repository.
+    pub fn document(&self, path: impl AsRef<Path>) -> Option<&Document> {
+        self.documents.get(&absolute_path(path.as_ref()))
+    }
+
+    /// The workflows of the repository, with their paths.
+    pub fn workflows(&self) -> impl Iterator<Item = (&Path, &Workflow)> {
+        self.documents
+            .iter()
+            .filter_map(|(path, document)| Some((path.as_path(), document.as_workflow()?)))
+    }
+
+    /// The action metadata files of the repository, with their paths.
+    pub fn actions(&self) -> impl Iterator<Item = (&Path, &Action)> {
+        self.documents
+            .iter()
+            .filter_map(|(path, document)| Some((path.as_path(), document.as_action()?)))
+    }
+
+    /// The local action (e.g. `./.github/actions/foo`) that is used by a step, if it is part of
+    /// the repository.
+    pub fn local_action(&self, uses: &ActionRef) -> Option<&Action> {
+        let ActionRef::Local { path } = uses else {
+            return None;
+        };
+        let directory = self.root.join(path);
+        ACTION_FILE_NAMES
+            .into_iter()
+            .find_map(|file_name| self.document(directory.join(file_name))?.as_action())
+    }
+
+    /// The local reusable workflow (e.g. `./.github/workflows/build.yml`) that is called by a
+    /// job, if it is part of the repository.
+    pub fn local_workflow(&self, uses: &ActionRef) -> Option<&Workflow> {
+        let ActionRef::Local { path } = uses else {
+            return None;
+        };
+        self.document(self.root.join(path))?.as_workflow()
+    }
+
+    /// The jobs in the repository that call the reusable workflow at the given path.
+    pub fn callers(&self, path: impl AsRef<Path>) -> Vec<WorkflowCall<'_>> {
+        let path = absolute_path(path.as_ref());
+        let Some(callee) = self.document(&path).and_then(Document::as_workflow) else {
+            return vec![];
+        };
+        self.workflows()
+            .flat_map(|(caller_path, caller)| {
+                caller
+                    .jobs
+                    .iter()
+                    .filter_map(move |(job_name, job)| match &**job {
+                        Job::Call(job) => Some((caller_path, caller, job_name, job.as_ref())),
+                        Job::Normal(_) => None,
+                    })
+            })
+            .filter(|(_caller_path, _caller, _job_name, job)| match &*job.uses {
+                ActionRef::Local { path: uses } => absolute_path(&self.root.join(uses)) == path,
+                _ => false,
+            })
+            .map(|(caller_path, caller, job_name, job)| WorkflowCall {
+                path: caller_path,
+                workflow: caller,
+                job_name,
+                job,
+                callee,
+            })
+            .collect()
+    }
+
+    /// Returns the path relative to the root of the repository (if possible), e.g. for messages.
+    pub fn relative_path<'a>(&self, path: &'a Path) -> &'a Path {
+        path.strip_prefix(&self.root).unwrap_or(path)
+    }
+}
+
+/// A job that calls a local reusable workflow.
+///
+/// The inputs and secrets that the job passes (`with` and `secrets`) are mapped to the inputs and
+/// secrets that the called workflow declares (`on.workflow_call.inputs` and
+/// `on.workflow_call.secrets`).
+#[derive(Debug, Clone, Copy)]
+pub struct WorkflowCall<'a> {
+    /// The path of the calling workflow.
+    pub path: &'a Path,
+    /// The calling workflow.
+    pub workflow: &'a Workflow,
+    /// The ID of the calling job.
+    pub job_name: &'a str,
+    /// The calling job.
+    pub job: &'a ReusableWorkflowCallJob,
+    /// The called workflow.
+    pub callee: &'a Workflow,
+}
+
+/// A secret that is passed to a reusable workflow.
+#[derive(Debug, Clone, Copy)]
+pub enum CallSecret<'a> {
+    /// All secrets of the calling workflow are passed (`secrets: inherit`).
+    Inherited,
+    /// The secret is set to the given value (usually an expression).
+    Value(&'a Spanned<String>),
+}
+
+impl<'a> WorkflowCall<'a> {
+    /// The value that the job passes to the input of the called workflow, if the input is
+    /// declared by the called workflow and set by the job.
+    ///
+    /// Like contexts, input names are case-insensitive.
+    pub fn input(&self, name: &str) -> Option<&'a Spanned<String>> {
+        let is_declared = self
+            .callee
+            .workflow_call()
+            .and_then(|workflow_call| workflow_call.inputs.as_ref())
+            .is_some_and(|inputs| inputs.keys().any(|key| key.eq_ignore_ascii_case(name)));
+        if !is_declared {
+            return None;
+        }
+        self.job
+            .with
+            .as_ref()?
+            .iter()
+            .find(|(key, _value)| key.eq_ignore_ascii_case(name))
+            .map(|(_key, value)| value)
+    }
+
+    /// The value that the job passes to the secret of the called workflow, if the secret is
+    /// available to the called workflow.
+    pub fn secret(&self, name: &str) -> Option<CallSecret<'a>> {
+        match &**self.job.secrets.as_ref()? {
+            JobSecrets::Inherit => Some(CallSecret::Inherited),
+            JobSecrets::Map(secrets) => {
+                let is_declared = self
+                    .callee
+                    .workflow_call()
+                    .and_then(|workflow_call| workflow_call.secrets.as_ref())
+                    .is_some_and(|declared| {
+                        declared.keys().any(|key| key.eq_ignore_ascii_case(name))
+                    });
+                secrets
+                    .iter()
+                    .find(|(key, _value)| is_declared && key.eq_ignore_ascii_case(name))
+                    .map(|(_key, value)| CallSecret::Value(value))
+            }
+        }
+    }
+
+    /// The token permissions of the calling job (or its workflow), which the permissions of the
+    /// called workflow cannot exceed.
+    pub fn permissions(&self) -> Option<&'a Spanned<Permissions>> {
+        self.job
+            .permissions
+            .as_ref()
+            .or(self.workflow.permissions.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn document(source: &str, kind: FileKind) -> Document {
+        Document::from_reader(&mut source.as_bytes(), kind).unwrap()
+    }
+
+    #[test]
+    fn test_local_references() {
+        let mut repository = Repository::new("/repo");
+        repository.insert(
+            "/repo/.github/workflows/build.yml",
+            document(
+                "on: workflow_call\njobs:\n  build:\n    runs-on: ubuntu-latest\n    steps:\n      - run: make\n",
+                FileKind::Workflow,
+            ),
+        );
+        repository.insert(
+            "/repo/.github/actions/greet/action.yaml",
+            document(
+                "name: Greet\nruns:\n  using: composite\n  steps:\n    - run: echo Hello\n      shell: bash\n",
+                FileKind::Action,
+            ),
+        );
+        assert_eq!(repository.workflows().count(), 1);
+        assert_eq!(repository.actions().count(), 1);
+
+        let uses: ActionRef = "./.github/actions/greet".parse().unwrap();
+        let action = repository.local_action(&uses).unwrap();
+        assert_eq!(action.name.as_ref().unwrap().as_str(), "Greet");
+        assert!(repository.local_workflow(&uses).is_none());
+
+        let uses: ActionRef = "./.github/workflows/build.yml".parse().unwrap();
+        assert!(repository.local_workflow(&uses).is_some());
+        assert!(repository
+            .callers("/repo/.github/workflows/build.yml")
+            .is_empty());
+        let uses: ActionRef = "octo-org/workflows/.github/workflows/build.yml@v1"
+            .parse()
+            .unwrap();
+        assert!(repository.local_workflow(&uses).is_none());
+    }
+
+    #[test]
+    fn test_callers() {
+        let mut repository = Repository::new("/repo");
+        repository.insert(
+            "/repo/.github/workflows/build.yml",
+            document(
+                "on:\n  workflow_call:\n    inputs:\n      target:\n        type: string\n    secrets:\n      token:\n        required: true\njobs:\n  build:\n    runs-on: ubuntu-latest\n    steps:\n      - run: make\n",
+                FileKind::Workflow,
+            ),
+        );
+        repository.insert(
+            "/repo/.github/workflows/ci.yml",
+            document(
+                "on: push\npermissions: {}\njobs:\n  build:\n    uses: ./.github/workflows/build.yml\n    with:\n      Target: release\n      unknown: foo\n    secrets:\n      token: ${{ secrets.TOKEN }}\n  other:\n    uses: octo-org/workflows/.github/workflows/build.yml@v1\n",
+                FileKind::Workflow,
+            ),
+        );
+
+        let callers = repository.callers("/repo/.github/workflows/./build.yml");
+        assert_eq!(callers.len(), 1);
+        let call = callers[0];
+        assert_eq!(
+            repository.relative_path(call.path),
+            Path::new(".github/workflows/ci.yml")
+        );
+        assert_eq!(call.job_name, "build");
+        assert_eq!(
+            call.input("target").map(|value| value.as_str()),
+            Some("release")
+        );
+        assert!(call.input("unknown").is_none());
+        assert!(matches!(call.secret("token"), Some(CallSecret::Value(_))));
+        assert!(call.secret("other").is_none());
+        assert!(call.permissions().is_some());
+    }
+}
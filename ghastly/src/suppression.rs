@@ -0,0 +1,200 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Inline suppression comments, e.g. `# ghastly: ignore[permissions_set] reason="..."`.
+//!
+//! A suppression comment applies to:
+//!
+//! - the line it is on, if there is content before the comment,
+//! - the whole file, if it is on its own line before any content,
+//! - otherwise, the next line with content.
+//!
+//! If that line is a mapping key without a value on the same line (e.g. `build:`), the
+//! suppression also applies to the line where the value starts.
+
+use crate::policies::PolicyViolation;
+use marked_yaml::{Marker, Span};
+
+/// The lines that a suppression applies to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Scope {
+    File,
+    Lines(Vec<usize>),
+}
+
+/// A suppression comment.
+#[derive(Debug, Clone)]
+pub struct Suppression {
+    /// The line of the comment.
+    line: usize,
+    /// The column of the comment.
+    column: usize,
+    /// The length (in characters) of the comment.
+    length: usize,
+    /// The names of the suppressed policies.
+    policies: Vec<String>,
+    /// The reason for the suppression (if any).
+    reason: Option<String>,
+    scope: Scope,
+}
+
+impl Suppression {
+    /// The names of the suppressed policies.
+    pub fn policies(&self) -> impl Iterator<Item = &str> {
+        self.policies.iter().map(String::as_str)
+    }
+
+    /// The reason for the suppression (if any).
+    pub fn reason(&self) -> Option<&str> {
+        self.reason.as_deref()
+    }
+
+    /// Returns `true` if this suppresses the given violation of the given policy.
+    pub fn suppresses(&self, policy_name: &str, violation: &PolicyViolation) -> bool {
+        if !self.policies().any(|name| name == policy_name) {
+            return false;
+        }
+        match &self.scope {
+            Scope::File => true,
+            Scope::Lines(lines) => violation
+                .source()
+                .start()
+                .is_some_and(|marker| lines.contains(&marker.line())),
+        }
+    }
+
+    /// The location of the comment.
+    pub fn span(&self) -> Span {
+        Span::new_with_marks(
+            Marker::new(0, self.line, self.column),
+            Marker::new(0, self.line, self.column + self.length),
+        )
+    }
+}
+
+/// Parses a suppression comment in the given line.
+///
+/// Returns the byte offset of the comment, the suppressed policies and the reason.
+fn parse_comment(line: &str) -> Option<(usize, Vec<String>, Option<String>)> {
+    line.match_indices('#')
+        .filter(|(offset, _)| {
+            line[..*offset]
+                .chars()
+                .next_back()
+                .is_none_or(char::is_whitespace)
+        })
+        .find_map(|(offset, _)| {
+            let comment = line[offset + 1..].trim_start();
+            let comment = comment.strip_prefix("ghastly:")?.trim_start();
+            let (policies, rest) = comment.strip_prefix("ignore[")?.split_once(']')?;
+            let policies = policies
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(str::to_owned)
+                .collect();
+            let reason = rest
+                .trim_start()
+                .strip_prefix("reason=")
+                .map(|reason| reason.trim().trim_matches('"').to_owned());
+            Some((offset, policies, reason))
+        })
+}
+
+/// Returns `true` if the line contains YAML content (i.e., it is not empty and not a comment).
+fn has_content(line: &str) -> bool {
+    let line = line.trim();
+    !line.is_empty() && !line.starts_with('#') && line != "---"
+}
+
+/// Returns the lines (1-based) that a suppression targeting the given line applies to.
+fn target_lines(lines: &[&str], index: usize) -> Vec<usize> {
+    let mut targets = vec![index + 1];
+    let is_key_without_value = lines[index]
+        .split(" #")
+        .next()
+        .is_some_and(|content| content.trim_end().ends_with(':'));
+    if is_key_without_value {
+        if let Some(value_index) = (index + 1..lines.len()).find(|&i| has_content(lines[i])) {
+            targets.push(value_index + 1);
+        }
+    }
+    targets
+}
+
+/// Finds all suppression comments in the given YAML source.
+pub fn parse_suppressions(source: &str) -> Vec<Suppression> {
+    let lines: Vec<&str> = source.lines().collect();
+    let first_content_index = lines.iter().position(|line| has_content(line));
+    lines
+        .iter()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let (offset, policies, reason) = parse_comment(line)?;
+            let scope = if has_content(&line[..offset]) {
+                Scope::Lines(target_lines(&lines, index))
+            } else if first_content_index.is_none_or(|first| index < first) {
+                Scope::File
+            } else {
+                let next_index = (index + 1..lines.len()).find(|&i| has_content(lines[i]))?;
+                Scope::Lines(target_lines(&lines, next_index))
+            };
+            Some(Suppression {
+                line: index + 1,
+                column: line[..offset].chars().count() + 1,
+                length: line[offset..].trim_end().chars().count(),
+                policies,
+                reason,
+                scope,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn violation(line: usize) -> PolicyViolation {
+        let marker = Marker::new(0, line, 1);
+        PolicyViolation::new(Span::new_with_marks(marker, marker), String::new())
+    }
+
+    #[test]
+    fn test_parse_suppressions() {
+        let source = r##"# ghastly: ignore[actions_pinned, permissions_set] reason="Legacy workflow"
+on: push
+jobs:
+  # ghastly: ignore[no_all_permissions]
+  build:
+    permissions: write-all # ghastly: ignore[permissions_set]
+    runs-on: ubuntu-latest
+    steps:
+      - run: echo "# ghastly: ignore[foo]"
+"##;
+        let suppressions = parse_suppressions(source);
+        assert_eq!(suppressions.len(), 3);
+
+        assert_eq!(suppressions[0].scope, Scope::File);
+        assert_eq!(
+            suppressions[0].policies().collect::<Vec<_>>(),
+            vec!["actions_pinned", "permissions_set"]
+        );
+        assert_eq!(suppressions[0].reason(), Some("Legacy workflow"));
+        assert!(suppressions[0].suppresses("actions_pinned", &violation(9)));
+        assert!(!suppressions[0].suppresses("no_all_permissions", &violation(9)));
+
+        assert_eq!(suppressions[1].scope, Scope::Lines(vec![5, 6]));
+        assert!(suppressions[1].suppresses("no_all_permissions", &violation(6)));
+        assert!(!suppressions[1].suppresses("no_all_permissions", &violation(7)));
+
+        assert_eq!(suppressions[2].scope, Scope::Lines(vec![6]));
+        assert_eq!((suppressions[2].line, suppressions[2].column), (6, 28));
+        assert_eq!(suppressions[2].reason(), None);
+    }
+}
@@ -0,0 +1,256 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+
+/// Runs `ghastly` with the arguments in the given working directory.
+fn ghastly(current_dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_ghastly"))
+        .current_dir(current_dir)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+/// Creates a Git repository with a reusable workflow that is called with the title of a pull
+/// request by a job that sets the permissions.
+fn create_repository(root: &Path) {
+    let workflows = root.join(".github/workflows");
+    fs::create_dir_all(root.join(".git")).unwrap();
+    fs::create_dir_all(&workflows).unwrap();
+    fs::write(
+        workflows.join("greet.yml"),
+        "on:\n  workflow_call:\n    inputs:\n      title:\n        type: string\njobs:\n  greet:\n    runs-on: ubuntu-latest\n    steps:\n      - run: echo \"${{ inputs.title }}\"\n",
+    )
+    .unwrap();
+    fs::write(
+        workflows.join("pr.yml"),
+        "on: [pull_request_target]\njobs:\n  call:\n    permissions:\n      contents: read\n    uses: ./.github/workflows/greet.yml\n    with:\n      title: ${{ github.event.pull_request.title }}\n",
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_check_from_outside_the_repository() {
+    let repository = tempfile::tempdir().unwrap();
+    let current_dir = tempfile::tempdir().unwrap();
+    create_repository(repository.path());
+
+    let path = repository.path().join(".github/workflows/greet.yml");
+    let output = ghastly(
+        current_dir.path(),
+        &["check", "--format", "text", path.to_str().unwrap()],
+    );
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout.contains("uses attacker-controlled context 'inputs.title' in the 'run' field (set to 'github.event.pull_request.title' by job call of '.github/workflows/pr.yml'). (no_untrusted_expr_in_run, high)"),
+        "{stdout}"
+    );
+    // The permissions of the calling job apply to the reusable workflow.
+    assert!(!stdout.contains("permissions_set"), "{stdout}");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.starts_with("Checked 1 file(s)"), "{stderr}");
+}
+
+#[test]
+fn test_config_from_outside_the_repository() {
+    let repository = tempfile::tempdir().unwrap();
+    let current_dir = tempfile::tempdir().unwrap();
+    create_repository(repository.path());
+    fs::write(
+        repository.path().join(".ghastly.toml"),
+        "[[overrides]]\npaths = [\".github/workflows/greet.yml\"]\n\n[overrides.policies.no_github_expr_in_run]\nenabled = true\n",
+    )
+    .unwrap();
+
+    let path = repository.path().join(".github/workflows/greet.yml");
+    let output = ghastly(
+        current_dir.path(),
+        &["check", "--format", "text", path.to_str().unwrap()],
+    );
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    // The policy is disabled by default, but enabled for the file by the configuration.
+    assert!(stdout.contains("no_github_expr_in_run"), "{stdout}");
+}
+
+#[test]
+fn test_baseline_with_other_path() {
+    let repository = tempfile::tempdir().unwrap();
+    create_repository(repository.path());
+    let baseline = repository.path().join("baseline.json");
+    let baseline = baseline.to_str().unwrap();
+
+    let output = ghastly(
+        repository.path(),
+        &[
+            "check",
+            "--write-baseline",
+            baseline,
+            ".github/workflows/greet.yml",
+        ],
+    );
+    assert!(output.status.success());
+
+    // The same file, passed with an absolute path from a subdirectory of the repository.
+    let path = repository.path().join(".github/workflows/greet.yml");
+    let output = ghastly(
+        &repository.path().join(".github"),
+        &[
+            "check",
+            "--format",
+            "text",
+            "--baseline",
+            baseline,
+            path.to_str().unwrap(),
+        ],
+    );
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.is_empty(), "{stdout}");
+}
+
+/// Creates a Git repository with a workflow that contains the step.
+fn create_workflow(root: &Path, step: &str) -> String {
+    let workflows = root.join(".github/workflows");
+    fs::create_dir_all(root.join(".git")).unwrap();
+    fs::create_dir_all(&workflows).unwrap();
+    fs::write(
+        workflows.join("ci.yml"),
+        format!("on: [push]\npermissions: {{}}\njobs:\n  test:\n    runs-on: ubuntu-latest\n    steps:\n      - {step}\n"),
+    )
+    .unwrap();
+    ".github/workflows/ci.yml".to_owned()
+}
+
+#[test]
+fn test_exit_codes() {
+    let repository = tempfile::tempdir().unwrap();
+
+    let path = create_workflow(repository.path(), "run: echo test");
+    let output = ghastly(repository.path(), &["check", "--format", "text", &path]);
+    assert_eq!(output.status.code(), Some(0));
+
+    let path = create_workflow(
+        repository.path(),
+        "run: echo \"${{ github.event.head_commit.message }}\"",
+    );
+    let output = ghastly(repository.path(), &["check", "--format", "text", &path]);
+    assert_eq!(output.status.code(), Some(1));
+
+    let output = ghastly(
+        repository.path(),
+        &["check", "--format", "text", "missing.yml"],
+    );
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn test_fail_on() {
+    let repository = tempfile::tempdir().unwrap();
+
+    // Unused suppressions are only reported with info severity, which does not fail by default.
+    let path = create_workflow(
+        repository.path(),
+        "run: echo test # ghastly: ignore[no_untrusted_expr_in_run]",
+    );
+    let output = ghastly(repository.path(), &["check", "--format", "text", &path]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("(unused_suppression, info)"), "{stdout}");
+    assert_eq!(output.status.code(), Some(0));
+    let output = ghastly(
+        repository.path(),
+        &["check", "--format", "text", "--fail-on", "info", &path],
+    );
+    assert_eq!(output.status.code(), Some(1));
+
+    // Inputs are only potentially attacker-controlled, which is reported with medium severity.
+    let path = create_workflow(repository.path(), "run: echo \"${{ inputs.name }}\"");
+    let output = ghastly(
+        repository.path(),
+        &["check", "--format", "text", "--fail-on", "medium", &path],
+    );
+    assert_eq!(output.status.code(), Some(1));
+    let output = ghastly(
+        repository.path(),
+        &["check", "--format", "text", "--fail-on", "high", &path],
+    );
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout.contains("(no_untrusted_expr_in_run, medium)"),
+        "{stdout}"
+    );
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn test_list_and_show() {
+    let current_dir = tempfile::tempdir().unwrap();
+
+    let output = ghastly(current_dir.path(), &["list"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let line = stdout
+        .lines()
+        .find(|line| line.starts_with("no_github_expr_in_run "))
+        .unwrap();
+    assert_eq!(
+        line.split_whitespace().collect::<Vec<_>>(),
+        ["no_github_expr_in_run", "low", "low", "injection"]
+    );
+
+    let output = ghastly(current_dir.path(), &["show", "no_github_expr_in_run"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    for (label, value) in [
+        ("Severity:", "low"),
+        ("Confidence:", "low"),
+        ("Category:", "injection"),
+        ("Tags:", "expressions"),
+        ("Enabled by default:", "no"),
+    ] {
+        assert!(
+            stdout
+                .lines()
+                .any(|line| line.contains(label) && line.ends_with(value)),
+            "{stdout}"
+        );
+    }
+    assert!(stdout.contains("No step should be using"), "{stdout}");
+
+    let output = ghastly(current_dir.path(), &["show", "no_such_policy"]);
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn test_check_from_subdirectory() {
+    let repository = tempfile::tempdir().unwrap();
+    create_workflow(
+        repository.path(),
+        "run: echo \"${{ github.event.head_commit.message }}\"",
+    );
+
+    for (current_dir, path) in [(".github/workflows", "."), (".github", "workflows")] {
+        let output = ghastly(
+            &repository.path().join(current_dir),
+            &["check", "--format", "text", path],
+        );
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.starts_with("Checked 1 file(s)"), "{stderr}");
+        assert_eq!(output.status.code(), Some(1));
+    }
+
+    let output = ghastly(repository.path(), &["check", "--format", "text", ".git"]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.starts_with("warning: no workflow or action metadata files found"),
+        "{stderr}"
+    );
+    assert_eq!(output.status.code(), Some(0));
+}